@@ -1,6 +1,6 @@
 use node_template_runtime::{
 	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	SystemConfig, TokenModuleConfig, WASM_BINARY,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -36,6 +36,15 @@ pub fn authority_keys_from_seed(s: &str) -> (AuraId, GrandpaId) {
 	(get_from_seed::<AuraId>(s), get_from_seed::<GrandpaId>(s))
 }
 
+/// Assets preset on development chains: id, creator, name, symbol, decimals.
+pub fn preset_assets(creator: AccountId) -> Vec<(u32, AccountId, Vec<u8>, Vec<u8>, u8)> {
+	vec![
+		(1, creator.clone(), b"Meter".to_vec(), b"MTR".to_vec(), 12),
+		(2, creator.clone(), b"Standard".to_vec(), b"STND".to_vec(), 12),
+		(3, creator, b"Tether USD".to_vec(), b"USDT".to_vec(), 12),
+	]
+}
+
 pub fn development_config() -> Result<ChainSpec, String> {
 	let wasm_binary = WASM_BINARY.ok_or_else(|| "Development wasm not available".to_string())?;
 
@@ -59,6 +68,8 @@ pub fn development_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 				],
+				// Preset assets
+				preset_assets(get_account_id_from_seed::<sr25519::Public>("Alice")),
 				true,
 			)
 		},
@@ -107,6 +118,8 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 				],
+				// Preset assets
+				preset_assets(get_account_id_from_seed::<sr25519::Public>("Alice")),
 				true,
 			)
 		},
@@ -130,6 +143,7 @@ fn testnet_genesis(
 	initial_authorities: Vec<(AuraId, GrandpaId)>,
 	root_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
+	assets: Vec<(u32, AccountId, Vec<u8>, Vec<u8>, u8)>,
 	_enable_println: bool,
 ) -> GenesisConfig {
 	GenesisConfig {
@@ -152,5 +166,13 @@ fn testnet_genesis(
 			key: Some(root_key),
 		},
 		transaction_payment: Default::default(),
+		token_module: TokenModuleConfig {
+			// Configure endowed accounts with initial balance of 1 << 60 of every preset asset.
+			accounts: assets
+				.iter()
+				.flat_map(|(id, ..)| endowed_accounts.iter().cloned().map(move |k| (*id, k, 1 << 60)))
+				.collect(),
+			assets,
+		},
 	}
 }
@@ -0,0 +1,319 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Pay transaction fees in pallet-token assets, modeled on pallet-asset-tx-payment. The native
+//! fee is quoted in the chosen asset through its pallet-market pool against the native currency,
+//! held from the payer before dispatch, and after dispatch the unused part is refunded while the
+//! rest is swapped through the pool into the fee pot. Should that swap fail, the share of the
+//! held asset that the actual fee is of the estimated one is paid to the fee pot as is, and the
+//! rest is still refunded.
+
+pub use pallet::*;
+
+use codec::{Decode, Encode};
+use frame_support::dispatch::{DispatchInfo, DispatchResult, PostDispatchInfo};
+use pallet_transaction_payment::{ChargeTransactionPayment, OnChargeTransaction};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{
+		DispatchInfoOf, Dispatchable, PostDispatchInfoOf, SignedExtension, Zero,
+	},
+	transaction_validity::{
+		InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+	},
+	FixedPointOperand,
+};
+
+// Native fee balance and withdrawn liquidity of the runtime's transaction payment.
+pub type BalanceOf<T> = <<T as pallet_transaction_payment::Config>::OnChargeTransaction as OnChargeTransaction<T>>::Balance;
+pub type LiquidityInfoOf<T> = <<T as pallet_transaction_payment::Config>::OnChargeTransaction as OnChargeTransaction<T>>::LiquidityInfo;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{pallet_prelude::*, transactional, PalletId};
+	use sp_runtime::traits::{AccountIdConversion, UniqueSaturatedInto, UniqueSaturatedFrom, Zero};
+	use sp_core::U256;
+	use pallet_token as token;
+	use pallet_market as market;
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + pallet_transaction_payment::Config + market::Config {
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// Identifies the fee pot receiving the native currency bought with asset fees.
+		#[pallet::constant]
+		type FeePalletId: Get<PalletId>;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A transaction fee was paid in an asset. \[who, asset_id, asset_paid, native_fee]
+		AssetTxFeePaid(T::AccountId, T::AssetId, T::Balance, T::Balance),
+		/// A fee held in an asset could not be swapped and was charged in that asset.
+		/// \[who, asset_id, asset_paid]
+		AssetTxFeeKept(T::AccountId, T::AssetId, T::Balance),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The asset has no pool with reserves against the native currency
+		NoPool,
+		/// The held asset no longer buys the actual fee from the pool
+		FeeNotCovered,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {}
+
+	impl<T: Config> Pallet<T> {
+		/// The account collecting the native currency bought with asset fees.
+		pub fn fee_pot() -> T::AccountId {
+			T::FeePalletId::get().into_account_truncating()
+		}
+
+		// The pool of asset `id` against the native currency with its (native, asset) reserves.
+		fn native_pool(id: T::AssetId) -> Option<(T::AssetId, T::Balance, T::Balance)> {
+			let lpt = market::Pallet::<T>::pair((id, Zero::zero()))?;
+			// Reserves are ordered by asset id, so the native currency always comes first
+			let (reserve_native, reserve_asset) = market::Pallet::<T>::reserves(lpt);
+			if reserve_native.is_zero() || reserve_asset.is_zero() {
+				return None
+			}
+			Some((lpt, reserve_native, reserve_asset))
+		}
+
+		/// Amount of asset `id` that buys a native `fee` from its pool against the native currency,
+		/// rounded up.
+		pub fn quote_fee(id: T::AssetId, fee: T::Balance) -> Option<T::Balance> {
+			let (lpt, reserve_native, reserve_asset) = Self::native_pool(id)?;
+			if fee >= reserve_native {
+				return None
+			}
+			Some(market::Pallet::<T>::_get_amount_in(&fee, &reserve_asset, &reserve_native, market::Pallet::<T>::pool_fee(lpt)))
+		}
+
+		// Hold the quote for `fee` in asset `id` from `who` until the actual fee is known.
+		pub(crate) fn withdraw_asset_fee(
+			who: &T::AccountId,
+			id: T::AssetId,
+			fee: T::Balance,
+		) -> Result<T::Balance, TransactionValidityError> {
+			let held = Self::quote_fee(id, fee).ok_or(InvalidTransaction::Payment)?;
+			token::Pallet::<T>::transfer_to_system(&id, who, &held)
+				.map_err(|_| InvalidTransaction::Payment)?;
+			Ok(held)
+		}
+
+		// Swap what buys `actual_fee` out of the `held` asset into the fee pot and refund the rest
+		// to `who`. Should the swap fail, charge the share of `held` that `actual_fee` is of the
+		// estimated `fee` in the asset itself and refund the rest.
+		pub(crate) fn correct_and_swap_asset_fee(
+			who: &T::AccountId,
+			id: T::AssetId,
+			held: T::Balance,
+			fee: T::Balance,
+			actual_fee: T::Balance,
+		) {
+			if Self::swap_asset_fee(who, id, held, actual_fee).is_ok() || held.is_zero() {
+				return
+			}
+			let mut charged = match fee.is_zero() {
+				true => held,
+				false => {
+					let fee_256 = market::Pallet::<T>::to_u256(&fee);
+					// Round up so refunds never exceed what was held
+					let charged_256 = (market::Pallet::<T>::to_u256(&held) * market::Pallet::<T>::to_u256(&actual_fee)
+						+ fee_256 - U256::one()) / fee_256;
+					T::Balance::unique_saturated_from(charged_256.min(U256::from(u128::MAX)).as_u128()).min(held)
+				},
+			};
+			let refund = held - charged;
+			if !refund.is_zero() && token::Pallet::<T>::transfer_from_system(&id, who, &refund).is_err() {
+				charged = held;
+			}
+			// The charge stays with the chain either way, in the token pallet account should the
+			// fee pot not accept it
+			if !charged.is_zero() {
+				let _ = token::Pallet::<T>::transfer_from_system(&id, &Self::fee_pot(), &charged);
+			}
+			Self::deposit_event(Event::AssetTxFeeKept(who.clone(), id, charged));
+		}
+
+		// Buy `actual_fee` native from the pool of asset `id` at its current reserves with part of
+		// `held`, paying it into the fee pot, and refund the rest of `held` to `who`.
+		#[transactional]
+		fn swap_asset_fee(
+			who: &T::AccountId,
+			id: T::AssetId,
+			held: T::Balance,
+			actual_fee: T::Balance,
+		) -> DispatchResult {
+			if actual_fee.is_zero() {
+				if !held.is_zero() {
+					token::Pallet::<T>::transfer_from_system(&id, who, &held)?;
+				}
+				return Ok(())
+			}
+			// The pool may have moved or been drained while the transaction was dispatched
+			let (lpt, reserve_native, reserve_asset) = Self::native_pool(id).ok_or(Error::<T>::NoPool)?;
+			ensure!(actual_fee < reserve_native, Error::<T>::FeeNotCovered);
+			let pool_fee = market::Pallet::<T>::pool_fee(lpt);
+			let charged = market::Pallet::<T>::_get_amount_in(&actual_fee, &reserve_asset, &reserve_native, pool_fee);
+			ensure!(charged <= held, Error::<T>::FeeNotCovered);
+			let native_out = market::Pallet::<T>::_get_amount_out(&charged, &reserve_asset, &reserve_native, pool_fee);
+			ensure!(native_out >= actual_fee, Error::<T>::FeeNotCovered);
+
+			let refund = held - charged;
+			if !refund.is_zero() {
+				token::Pallet::<T>::transfer_from_system(&id, who, &refund)?;
+			}
+			market::Pallet::<T>::_set_reserves(&id, &Zero::zero(), &(reserve_asset + charged), &(reserve_native - native_out), &lpt);
+			token::Pallet::<T>::transfer_from_system(&Zero::zero(), &Self::fee_pot(), &native_out)?;
+			Self::deposit_event(Event::AssetTxFeePaid(who.clone(), id, charged, actual_fee));
+			Ok(())
+		}
+
+		// Convert a balance of the transaction payment into a pallet-token balance.
+		pub(crate) fn to_asset_balance(amount: crate::BalanceOf<T>) -> T::Balance {
+			T::Balance::unique_saturated_from(UniqueSaturatedInto::<u128>::unique_saturated_into(amount))
+		}
+	}
+}
+
+/// How the fee of a transaction was paid before dispatch.
+pub enum InitialPayment<T: Config> {
+	/// No fee was due
+	Nothing,
+	/// The fee was withdrawn in the native currency
+	Native(LiquidityInfoOf<T>),
+	/// The quote of the estimated fee is held in an asset: asset id, held amount, native fee
+	Asset(T::AssetId, T::Balance, BalanceOf<T>),
+}
+
+/// Charges the transaction fee in the native currency, or in `asset_id` when one is given.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+pub struct ChargeAssetTxPayment<T: Config> {
+	#[codec(compact)]
+	tip: BalanceOf<T>,
+	asset_id: Option<T::AssetId>,
+}
+
+impl<T: Config> ChargeAssetTxPayment<T>
+where
+	T::RuntimeCall: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+	BalanceOf<T>: Send + Sync + FixedPointOperand,
+{
+	/// Pay `tip` on top of the fee, in asset `asset_id` or the native currency if `None`.
+	pub fn from(tip: BalanceOf<T>, asset_id: Option<T::AssetId>) -> Self {
+		Self { tip, asset_id }
+	}
+
+	fn withdraw_fee(
+		&self,
+		who: &T::AccountId,
+		call: &T::RuntimeCall,
+		info: &DispatchInfoOf<T::RuntimeCall>,
+		len: usize,
+	) -> Result<(BalanceOf<T>, InitialPayment<T>), TransactionValidityError> {
+		let fee = pallet_transaction_payment::Pallet::<T>::compute_fee(len as u32, info, self.tip);
+		if fee.is_zero() {
+			return Ok((fee, InitialPayment::Nothing))
+		}
+		match self.asset_id {
+			Some(id) if !id.is_zero() => {
+				let held = Pallet::<T>::withdraw_asset_fee(who, id, Pallet::<T>::to_asset_balance(fee))?;
+				Ok((fee, InitialPayment::Asset(id, held, fee)))
+			},
+			_ => <T::OnChargeTransaction as OnChargeTransaction<T>>::withdraw_fee(who, call, info, fee, self.tip)
+				.map(|liquidity| (fee, InitialPayment::Native(liquidity))),
+		}
+	}
+}
+
+impl<T: Config> sp_std::fmt::Debug for ChargeAssetTxPayment<T> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "ChargeAssetTxPayment<{:?}, {:?}>", self.tip, self.asset_id.encode())
+	}
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T: Config> SignedExtension for ChargeAssetTxPayment<T>
+where
+	T::RuntimeCall: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+	BalanceOf<T>: Send + Sync + FixedPointOperand,
+	T::AssetId: Send + Sync,
+{
+	const IDENTIFIER: &'static str = "ChargeAssetTxPayment";
+	type AccountId = T::AccountId;
+	type Call = T::RuntimeCall;
+	type AdditionalSigned = ();
+	type Pre = (BalanceOf<T>, Self::AccountId, InitialPayment<T>);
+
+	fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> TransactionValidity {
+		let (fee, _) = self.withdraw_fee(who, call, info, len)?;
+		let priority = ChargeTransactionPayment::<T>::get_priority(info, len, self.tip, fee);
+		Ok(ValidTransaction { priority, ..Default::default() })
+	}
+
+	fn pre_dispatch(
+		self,
+		who: &Self::AccountId,
+		call: &Self::Call,
+		info: &DispatchInfoOf<Self::Call>,
+		len: usize,
+	) -> Result<Self::Pre, TransactionValidityError> {
+		let (_, initial_payment) = self.withdraw_fee(who, call, info, len)?;
+		Ok((self.tip, who.clone(), initial_payment))
+	}
+
+	fn post_dispatch(
+		pre: Option<Self::Pre>,
+		info: &DispatchInfoOf<Self::Call>,
+		post_info: &PostDispatchInfoOf<Self::Call>,
+		len: usize,
+		_result: &DispatchResult,
+	) -> Result<(), TransactionValidityError> {
+		let (tip, who, initial_payment) = match pre {
+			Some(pre) => pre,
+			None => return Ok(()),
+		};
+		let actual_fee = pallet_transaction_payment::Pallet::<T>::compute_actual_fee(len as u32, info, post_info, tip);
+		match initial_payment {
+			InitialPayment::Nothing => Ok(()),
+			InitialPayment::Native(already_withdrawn) => {
+				<T::OnChargeTransaction as OnChargeTransaction<T>>::correct_and_deposit_fee(
+					&who, info, post_info, actual_fee, tip, already_withdrawn,
+				)
+			},
+			InitialPayment::Asset(id, held, fee) => {
+				Pallet::<T>::correct_and_swap_asset_fee(
+					&who,
+					id,
+					held,
+					Pallet::<T>::to_asset_balance(fee),
+					Pallet::<T>::to_asset_balance(actual_fee),
+				);
+				Ok(())
+			},
+		}
+	}
+}
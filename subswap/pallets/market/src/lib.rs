@@ -6,10 +6,14 @@ mod math;
 pub mod pallet {
 	use frame_support::pallet_prelude::*;
 	use frame_system::pallet_prelude::*;
-	use sp_runtime::{FixedU128,traits::{UniqueSaturatedInto,UniqueSaturatedFrom,CheckedMul, CheckedAdd, CheckedDiv, CheckedSub, Zero, One}};
+	use sp_runtime::{FixedU128,traits::{UniqueSaturatedInto,UniqueSaturatedFrom,CheckedMul, CheckedAdd, CheckedDiv, CheckedSub, Zero}};
 	use pallet_token as token;
 	use sp_core::U256;
+	use sp_std::vec::Vec;
 	use crate::math;
+	/// Pool fees are expressed in basis points of the input amount.
+	pub const FEE_DENOMINATOR: u32 = 10_000;
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
 	pub struct Pallet<T>(_);
@@ -17,6 +21,26 @@ pub mod pallet {
 	#[pallet::config]
 	pub trait Config: frame_system::Config + token::Config {
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// The maximum number of assets in a `swap_route` path.
+		#[pallet::constant]
+		type MaxPathLength: Get<u32>;
+
+		/// The swap fees in basis points a pool may charge.
+		#[pallet::constant]
+		type FeeTiers: Get<Vec<u32>>;
+
+		/// The swap fee in basis points of pools created without choosing one, and of pools
+		/// created before fees were configurable.
+		#[pallet::constant]
+		type DefaultFee: Get<u32>;
+
+		/// The origin allowed to change the fee of a pool and where protocol fees go.
+		type GovernanceOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// While `FeeTo` is set, it earns `1 / ProtocolFeeDivisor` of the fees of every pool.
+		#[pallet::constant]
+		type ProtocolFeeDivisor: Get<u32>;
 	}
 	
 	#[pallet::storage]
@@ -56,7 +80,39 @@ pub mod pallet {
 		OptionQuery
 	>;
 
+	/// Lp tokens of the pools an asset is one side of. \[asset, lptoken]
+	#[pallet::storage]
+	pub(super) type PoolsOf<T: Config> = StorageDoubleMap<
+		_, 
+		Blake2_128Concat, T::AssetId, 
+		Blake2_128Concat, T::AssetId, 
+		(), 
+		OptionQuery
+	>;
 
+	/// Swap fee of a pool in basis points, `DefaultFee` if unset. \[lptoken]
+	#[pallet::storage]
+	pub(super) type PoolFees<T: Config> = StorageMap<
+		_, 
+		Blake2_128Concat, T::AssetId, 
+		u32, 
+		OptionQuery
+	>;
+
+	/// Account receiving the protocol fee as lp tokens, none while the fee is off.
+	#[pallet::storage]
+	#[pallet::getter(fn fee_to)]
+	pub(super) type FeeTo<T: Config> = StorageValue<_, T::AccountId, OptionQuery>;
+
+	/// Product of a pool's reserves after its last liquidity change while the protocol fee was on.
+	#[pallet::storage]
+	#[pallet::getter(fn k_last)]
+	pub(super) type KLast<T: Config> = StorageMap<
+		_, 
+		Blake2_128Concat, T::AssetId, 
+		U256, 
+		ValueQuery
+	>;
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
@@ -69,6 +125,13 @@ pub mod pallet {
 		MintedLiquidity(T::AssetId, T::AssetId, T::AssetId),
 		/// Liquidity is burned. \[lptoken, token0, token1]
 		BurnedLiquidity(T::AssetId, T::AssetId, T::AssetId),
+		/// The swap fee of a pool was set. \[lptoken, fee_bps]
+		PoolFeeSet(T::AssetId, u32),
+		/// The protocol fee recipient was set, or the fee turned off. \[fee_to]
+		FeeToSet(Option<T::AccountId>),
+		/// An asset is swapped to another asset through several pools.
+		/// \[from, amount_in, to, amount_out]
+		RouteSwapped(T::AssetId, T::Balance, T::AssetId, T::Balance),
 		/// Sync oracle. \[price0, price1]
         SyncOracle(FixedU128, FixedU128),
 	}
@@ -112,6 +175,26 @@ pub mod pallet {
         InsufficientLiquidity,
         /// The ratio does not match from previous K
         K,
+		/// The deadline block has passed
+		Expired,
+		/// The swap would take more than the maximum input amount
+		ExcessiveInputAmount,
+		/// The path has fewer than two assets or uses a pool twice
+		InvalidPath,
+		/// The fee is not one of the allowed fee tiers
+		InvalidFeeTier,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn integrity_test() {
+			let tiers = T::FeeTiers::get();
+			assert!(
+				tiers.iter().all(|tier| *tier < FEE_DENOMINATOR),
+				"every fee tier must be below FEE_DENOMINATOR",
+			);
+			assert!(tiers.contains(&T::DefaultFee::get()), "DefaultFee must be one of the FeeTiers");
+		}
 	}
 
 	// Dispatchable functions allows users to interact with the pallet and invoke state changes.
@@ -119,13 +202,26 @@ pub mod pallet {
 	// Dispatchable functions must be annotated with a weight and must return a DispatchResult.
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		/// An example dispatchable that takes a singles value as a parameter, writes the value to
-		/// storage and emits an event. This function must be dispatched by a signed extrinsic.
+		/// Deposit `amount0` of `token0` and `amount1` of `token1` into their pool, creating it if
+		/// needed with a swap fee of `fee_tier` basis points, or `DefaultFee` if `None`, and mint
+		/// lp tokens to the sender. `fee_tier` is ignored for existing pools. Fails with
+		/// `InsufficientOutputAmount` if fewer than `min_lp_out` lp tokens would be minted, or with
+		/// `Expired` after block `deadline`.
 		#[pallet::call_index(0)]
         #[pallet::weight(0)]
-		pub fn mint_liquidity(origin: OriginFor<T>, token0: T::AssetId, amount0: T::Balance, token1: T::AssetId, amount1: T::Balance) -> DispatchResult {
+		pub fn mint_liquidity(
+			origin: OriginFor<T>,
+			token0: T::AssetId,
+			amount0: T::Balance,
+			token1: T::AssetId,
+			amount1: T::Balance,
+			fee_tier: Option<u32>,
+			min_lp_out: T::Balance,
+			deadline: Option<T::BlockNumber>,
+		) -> DispatchResult {
             let minimum_liquidity = T::Balance::from(1u32);
             let sender = ensure_signed(origin)?;
+            Self::ensure_deadline(deadline)?;
             ensure!(token0 != token1, Error::<T>::IdenticalIdentifier);
             // Burn assets from user to deposit to reserves
             token::Pallet::<T>::transfer_to_system(&token0, &sender, &amount0)?;
@@ -135,24 +231,32 @@ pub mod pallet {
                 None => {
                     let mut lptoken_amount: T::Balance = math::sqrt::<T>(amount0 * amount1);
                     lptoken_amount = lptoken_amount.checked_sub(&minimum_liquidity).expect("Integer overflow");
+                    ensure!(lptoken_amount >= min_lp_out, Error::<T>::InsufficientOutputAmount);
+                    let fee = fee_tier.unwrap_or_else(T::DefaultFee::get);
+                    ensure!(T::FeeTiers::get().contains(&fee), Error::<T>::InvalidFeeTier);
                     // Issue LPtoken
-                    token::Pallet::<T>::issue_from_system(Zero::zero())?;
-                    let mut lptoken_id: T::AssetId = token::NextAssetId::<T>::get();
-                    lptoken_id -= One::one();
+                    let lptoken_id = token::Pallet::<T>::issue_from_system(Zero::zero())?;
+                    Self::_set_lp_metadata(&token0, &token1, &lptoken_id)?;
                     // Deposit assets to the reserve
                     Self::_set_reserves(&token0, &token1, &amount0, &amount1, &lptoken_id);
                     // Set pairs for swap lookup
                     Self::_set_pair(&token0, &token1, &lptoken_id);
                     Self::_set_rewards(&token0, &token1, &lptoken_id);
+                    <PoolFees<T>>::insert(lptoken_id, fee);
+                    if Self::fee_to().is_some() {
+                        Self::_update_k_last(&lptoken_id, &Self::reserves(lptoken_id));
+                    }
                     // Mint LPtoken to the sender
                     token::Pallet::<T>::mint_from_system(&lptoken_id, &sender, &lptoken_amount)?;
                     Self::deposit_event(Event::CreatePair(token0, token1, lptoken_id));
+                    Self::deposit_event(Event::PoolFeeSet(lptoken_id, fee));
                     Ok(())
                 },
                 // when lpt exists and total supply is bigger than 0
                 Some(lpt) if token::Pallet::<T>::total_supply(lpt) > Zero::zero() => {
-                    let total_supply = token::Pallet::<T>::total_supply(lpt);
                     let mut reserves = Self::reserves(lpt);
+                    let fee_on = Self::_mint_fee(&lpt, &reserves)?;
+                    let total_supply = token::Pallet::<T>::total_supply(lpt);
                     if token0 > token1 {
                         ensure!(math::absdiff::<T>(reserves.0/reserves.1 * amount0, amount1) < amount0.checked_div(&T::Balance::from(1000u32)).expect("Divide by zero error"), Error::<T>::K);
                     } else {
@@ -161,10 +265,14 @@ pub mod pallet {
                     let left = amount0.checked_mul(&total_supply).expect("Multiplicaiton overflow").checked_div(&reserves.0).expect("Divide by zero error");
                     let right = amount1.checked_mul(&total_supply).expect("Multiplicaiton overflow").checked_div(&reserves.1).expect("Divide by zero error");
                     let lptoken_amount = math::min::<T>(left, right);
+                    ensure!(lptoken_amount >= min_lp_out, Error::<T>::InsufficientOutputAmount);
                     // Deposit assets to the reserve
                     reserves.0 += amount0;
                     reserves.1 += amount1;
                     Self::_set_reserves(&token0, &token1, &reserves.0, &reserves.1, &lpt);
+                    if fee_on {
+                        Self::_update_k_last(&lpt, &Self::reserves(lpt));
+                    }
                     // Mint LPtoken to the sender
                     token::Pallet::<T>::mint_from_system(&lpt, &sender, &lptoken_amount)?;
                     Self::deposit_event(Event::MintedLiquidity(token0, token1, lpt));
@@ -179,12 +287,24 @@ pub mod pallet {
 		}
 
 
+		/// Burn `amount` of lp token `lpt` for a pro-rata share of its pool. Fails with
+		/// `InsufficientOutputAmount` if the share of the lower or higher asset id is below
+		/// `min_amount0` or `min_amount1`, or with `Expired` after block `deadline`.
 		#[pallet::call_index(1)]
         #[pallet::weight(0)]
-		pub fn burn_liquidity(origin: OriginFor<T>, lpt: T::AssetId, amount: T::Balance) -> DispatchResult{
+		pub fn burn_liquidity(
+			origin: OriginFor<T>,
+			lpt: T::AssetId,
+			amount: T::Balance,
+			min_amount0: T::Balance,
+			min_amount1: T::Balance,
+			deadline: Option<T::BlockNumber>,
+		) -> DispatchResult{
             let sender = ensure_signed(origin)?;
+            Self::ensure_deadline(deadline)?;
             let mut reserves = Self::reserves(lpt);
             let tokens = Self::reward(lpt);
+            let fee_on = Self::_mint_fee(&lpt, &reserves)?;
             let total_supply = token::Pallet::<T>::total_supply(lpt);
 
             // Calculate rewards for providing liquidity with pro-rata distribution
@@ -193,6 +313,7 @@ pub mod pallet {
 
             // Ensure rewards exist
             ensure!(reward0 > Zero::zero() && reward1 > Zero::zero(), Error::<T>::InsufficientLiquidityBurned);
+            ensure!(reward0 >= min_amount0 && reward1 >= min_amount1, Error::<T>::InsufficientOutputAmount);
 
             // Distribute reward to the sender
             token::Pallet::<T>::burn_from_system(&lpt, &sender, &amount)?;
@@ -203,6 +324,9 @@ pub mod pallet {
             reserves.0 -= reward0;
             reserves.1 -= reward1;
             Self::_set_reserves(&tokens.0, &tokens.1, &reserves.0, &reserves.1, &lpt);
+            if fee_on {
+                Self::_update_k_last(&lpt, &reserves);
+            }
             // Deposit event that the liquidity is burned successfully
             Self::deposit_event(Event::BurnedLiquidity(lpt, tokens.0, tokens.1));
             // Update price
@@ -211,10 +335,20 @@ pub mod pallet {
 		}
 
 		
+		/// Swap `amount_in` of `from` for `to`. Fails with `InsufficientOutputAmount` if less than
+		/// `min_amount_out` would be received, or with `Expired` after block `deadline`.
 		#[pallet::call_index(2)]
         #[pallet::weight(0)]
-		pub fn swap(origin: OriginFor<T>, from: T::AssetId, amount_in: T::Balance, to: T::AssetId) -> DispatchResult {
+		pub fn swap(
+			origin: OriginFor<T>,
+			from: T::AssetId,
+			amount_in: T::Balance,
+			to: T::AssetId,
+			min_amount_out: T::Balance,
+			deadline: Option<T::BlockNumber>,
+		) -> DispatchResult {
             let sender = ensure_signed(origin)?;
+            Self::ensure_deadline(deadline)?;
             ensure!(amount_in > Zero::zero(), Error::<T>::InsufficientAmount);
             // Find pair
             let lpt = Self::pair((from, to));
@@ -226,7 +360,8 @@ pub mod pallet {
                 false => (reserves.0, reserves.1)
             };
             // get amount out
-            let amount_out = Self::_get_amount_out(&amount_in, &reserve_in, &reserve_out);
+            let amount_out = Self::_get_amount_out(&amount_in, &reserve_in, &reserve_out, Self::pool_fee(lpt.unwrap()));
+            ensure!(amount_out >= min_amount_out, Error::<T>::InsufficientOutputAmount);
             // transfer amount in to system
             token::Pallet::<T>::transfer_to_system(&from, &sender, &amount_in)?;
             // transfer swapped amount
@@ -241,10 +376,192 @@ pub mod pallet {
             //Self::_update(&lpt.unwrap())?;
             Ok(())
         }
+
+		/// Swap at most `max_amount_in` of `from` for exactly `amount_out` of `to`. Fails with
+		/// `ExcessiveInputAmount` if more input would be needed, or with `Expired` after block
+		/// `deadline`.
+		#[pallet::call_index(3)]
+        #[pallet::weight(0)]
+		pub fn swap_exact_out(
+			origin: OriginFor<T>,
+			from: T::AssetId,
+			max_amount_in: T::Balance,
+			to: T::AssetId,
+			amount_out: T::Balance,
+			deadline: Option<T::BlockNumber>,
+		) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_deadline(deadline)?;
+            ensure!(amount_out > Zero::zero(), Error::<T>::InsufficientOutputAmount);
+            // Find pair
+            let lpt = Self::pair((from, to)).ok_or(Error::<T>::InvalidPair)?;
+            let reserves = Self::reserves(lpt);
+            let (mut reserve_in, mut reserve_out) = match from > to {
+                true => (reserves.1, reserves.0),
+                false => (reserves.0, reserves.1)
+            };
+            ensure!(reserve_in > Zero::zero() && amount_out < reserve_out, Error::<T>::InsufficientLiquidity);
+            // get amount in
+            let amount_in = Self::_get_amount_in(&amount_out, &reserve_in, &reserve_out, Self::pool_fee(lpt));
+            ensure!(amount_in <= max_amount_in, Error::<T>::ExcessiveInputAmount);
+            // transfer amount in to system
+            token::Pallet::<T>::transfer_to_system(&from, &sender, &amount_in)?;
+            // transfer swapped amount
+            token::Pallet::<T>::transfer_from_system(&to, &sender, &amount_out)?;
+            // update reserves
+            reserve_in += amount_in;
+            reserve_out -= amount_out;
+            Self::_set_reserves(&from, &to, &reserve_in, &reserve_out, &lpt);
+            Self::deposit_event(Event::Swap(from, amount_in, to, amount_out));
+            Ok(())
+        }
+
+		/// Swap `amount_in` of the first asset of `path` for the last one, through the pool of
+		/// every consecutive pair. Fails with `InsufficientOutputAmount` if less than `min_out`
+		/// would be received, or with `Expired` after block `deadline`.
+		#[pallet::call_index(4)]
+        #[pallet::weight(0)]
+		pub fn swap_route(
+			origin: OriginFor<T>,
+			path: BoundedVec<T::AssetId, T::MaxPathLength>,
+			amount_in: T::Balance,
+			min_out: T::Balance,
+			deadline: Option<T::BlockNumber>,
+		) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_deadline(deadline)?;
+            ensure!(amount_in > Zero::zero(), Error::<T>::InsufficientAmount);
+            let amounts = Self::_get_amounts_out(&amount_in, &path)?;
+            let amount_out = amounts[amounts.len() - 1];
+            ensure!(amount_out >= min_out, Error::<T>::InsufficientOutputAmount);
+            Self::_swap_route(&sender, &path, &amounts)
+        }
+
+		/// Swap at most `max_amount_in` of the first asset of `path` for exactly `amount_out` of
+		/// the last one, through the pool of every consecutive pair. Fails with
+		/// `ExcessiveInputAmount` if more input would be needed, or with `Expired` after block
+		/// `deadline`.
+		#[pallet::call_index(5)]
+        #[pallet::weight(0)]
+		pub fn swap_route_exact_out(
+			origin: OriginFor<T>,
+			path: BoundedVec<T::AssetId, T::MaxPathLength>,
+			amount_out: T::Balance,
+			max_amount_in: T::Balance,
+			deadline: Option<T::BlockNumber>,
+		) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_deadline(deadline)?;
+            ensure!(amount_out > Zero::zero(), Error::<T>::InsufficientOutputAmount);
+            let amounts = Self::_get_amounts_in(&amount_out, &path)?;
+            ensure!(amounts[0] <= max_amount_in, Error::<T>::ExcessiveInputAmount);
+            Self::_swap_route(&sender, &path, &amounts)
+        }
+
+		/// Set the swap fee of pool `lpt` to `fee` basis points, one of the allowed fee tiers.
+		#[pallet::call_index(6)]
+        #[pallet::weight(0)]
+		pub fn set_pool_fee(origin: OriginFor<T>, lpt: T::AssetId, fee: u32) -> DispatchResult {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+            ensure!(<Rewards<T>>::contains_key(lpt), Error::<T>::InvalidPair);
+            ensure!(T::FeeTiers::get().contains(&fee), Error::<T>::InvalidFeeTier);
+
+            <PoolFees<T>>::insert(lpt, fee);
+            Self::deposit_event(Event::PoolFeeSet(lpt, fee));
+            Ok(())
+        }
+
+		/// Send the protocol fee to `fee_to` as lp tokens, or turn it off with `None`.
+		#[pallet::call_index(7)]
+        #[pallet::weight(0)]
+		pub fn set_fee_to(origin: OriginFor<T>, fee_to: Option<T::AccountId>) -> DispatchResult {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+
+            <FeeTo<T>>::set(fee_to.clone());
+            Self::deposit_event(Event::FeeToSet(fee_to));
+            Ok(())
+        }
 	}
 
 
 	impl<T: Config> Pallet<T>  {
+		// Fail with `Expired` once the current block is past `deadline`
+		fn ensure_deadline(deadline: Option<T::BlockNumber>) -> DispatchResult {
+			if let Some(deadline) = deadline {
+				ensure!(frame_system::Pallet::<T>::block_number() <= deadline, Error::<T>::Expired);
+			}
+			Ok(())
+		}
+
+		// Pool between `from` and `to` with its reserves ordered as (reserve_in, reserve_out)
+		fn _pool(from: &T::AssetId, to: &T::AssetId) -> Result<(T::AssetId, T::Balance, T::Balance), DispatchError> {
+			let lpt = Self::pair((*from, *to)).ok_or(Error::<T>::InvalidPair)?;
+			let reserves = Self::reserves(lpt);
+			ensure!(reserves.0 > Zero::zero() && reserves.1 > Zero::zero(), Error::<T>::InsufficientLiquidity);
+			match *from > *to {
+				true => Ok((lpt, reserves.1, reserves.0)),
+				false => Ok((lpt, reserves.0, reserves.1)),
+			}
+		}
+
+		// Fail with `InvalidPath` unless `path` has at least two assets and no pool repeats, so
+		// amounts quoted up front stay valid while the hops execute
+		fn _ensure_path(path: &[T::AssetId]) -> DispatchResult {
+			ensure!(path.len() >= 2, Error::<T>::InvalidPath);
+			let mut pools = Vec::with_capacity(path.len() - 1);
+			for hop in path.windows(2) {
+				let lpt = Self::pair((hop[0], hop[1])).ok_or(Error::<T>::InvalidPair)?;
+				ensure!(!pools.contains(&lpt), Error::<T>::InvalidPath);
+				pools.push(lpt);
+			}
+			Ok(())
+		}
+
+		/// Amounts of every asset of `path` when swapping `amount_in` of the first one.
+		pub fn _get_amounts_out(amount_in: &T::Balance, path: &[T::AssetId]) -> Result<Vec<T::Balance>, DispatchError> {
+			Self::_ensure_path(path)?;
+			let mut amounts = Vec::with_capacity(path.len());
+			amounts.push(*amount_in);
+			for hop in path.windows(2) {
+				let (lpt, reserve_in, reserve_out) = Self::_pool(&hop[0], &hop[1])?;
+				let amount_out = Self::_get_amount_out(&amounts[amounts.len() - 1], &reserve_in, &reserve_out, Self::pool_fee(lpt));
+				ensure!(amount_out > Zero::zero(), Error::<T>::InsufficientOutputAmount);
+				amounts.push(amount_out);
+			}
+			Ok(amounts)
+		}
+
+		/// Amounts of every asset of `path` when buying `amount_out` of the last one.
+		pub fn _get_amounts_in(amount_out: &T::Balance, path: &[T::AssetId]) -> Result<Vec<T::Balance>, DispatchError> {
+			Self::_ensure_path(path)?;
+			let mut amounts = Vec::with_capacity(path.len());
+			amounts.push(*amount_out);
+			for hop in path.windows(2).rev() {
+				let (lpt, reserve_in, reserve_out) = Self::_pool(&hop[0], &hop[1])?;
+				let amount = amounts[amounts.len() - 1];
+				ensure!(amount < reserve_out, Error::<T>::InsufficientLiquidity);
+				amounts.push(Self::_get_amount_in(&amount, &reserve_in, &reserve_out, Self::pool_fee(lpt)));
+			}
+			amounts.reverse();
+			Ok(amounts)
+		}
+
+		// Take the first amount from `sender`, move every hop's amounts through its pool and pay
+		// out the last amount. Intermediate assets never leave the pallet account.
+		fn _swap_route(sender: &T::AccountId, path: &[T::AssetId], amounts: &[T::Balance]) -> DispatchResult {
+			let (from, to) = (path[0], path[path.len() - 1]);
+			let (amount_in, amount_out) = (amounts[0], amounts[amounts.len() - 1]);
+			token::Pallet::<T>::transfer_to_system(&from, sender, &amount_in)?;
+			for (hop, hop_amounts) in path.windows(2).zip(amounts.windows(2)) {
+				let (lpt, reserve_in, reserve_out) = Self::_pool(&hop[0], &hop[1])?;
+				Self::_set_reserves(&hop[0], &hop[1], &(reserve_in + hop_amounts[0]), &(reserve_out - hop_amounts[1]), &lpt);
+				Self::deposit_event(Event::Swap(hop[0], hop_amounts[0], hop[1], hop_amounts[1]));
+			}
+			token::Pallet::<T>::transfer_from_system(&to, sender, &amount_out)?;
+			Self::deposit_event(Event::RouteSwapped(from, amount_in, to, amount_out));
+			Ok(())
+		}
+
 		// Market methods
 		pub fn _set_reserves(
 			token0: &T::AssetId,
@@ -266,6 +583,8 @@ pub mod pallet {
 		fn _set_pair(token0: &T::AssetId, token1: &T::AssetId, lptoken: &T::AssetId) {
 			<Pairs<T>>::insert((*token0, *token1), *lptoken);
 			<Pairs<T>>::insert((*token1, *token0), *lptoken);
+			<PoolsOf<T>>::insert(*token0, *lptoken, ());
+			<PoolsOf<T>>::insert(*token1, *lptoken, ());
 		}
 
 		fn _set_rewards(
@@ -281,30 +600,101 @@ pub mod pallet {
 			}
 		}
 
+		fn _set_lp_metadata(
+			token0: &T::AssetId, token1: &T::AssetId, lptoken: &T::AssetId
+		) -> DispatchResult {
+			let (token0, token1) = match *token0 > *token1 {
+				true => (token1, token0),
+				_ => (token0, token1),
+			};
+			// LP-<sym0>-<sym1>
+			let mut symbol = b"LP-".to_vec();
+			symbol.extend(token::Pallet::<T>::symbol(token0));
+			symbol.push(b'-');
+			symbol.extend(token::Pallet::<T>::symbol(token1));
+			// Liquidity is minted as sqrt(amount0 * amount1), so it carries the mean of both
+			// decimals
+			let decimals = ((token::Pallet::<T>::decimals(token0) as u16 +
+				token::Pallet::<T>::decimals(token1) as u16) / 2) as u8;
+			token::Pallet::<T>::set_metadata_from_system(lptoken, symbol.clone(), symbol, decimals)
+		}
+
+		// Mint `FeeTo` its share of the fees pool `lpt` earned since `KLast`, as lp tokens diluting
+		// the other providers. Returns whether the protocol fee is on, so the caller updates
+		// `KLast` after changing the reserves.
+		fn _mint_fee(lpt: &T::AssetId, reserves: &(T::Balance, T::Balance)) -> Result<bool, DispatchError> {
+			let k_last = Self::k_last(lpt);
+			let fee_to = match Self::fee_to() {
+				Some(fee_to) => fee_to,
+				None => {
+					if !k_last.is_zero() {
+						<KLast<T>>::remove(lpt);
+					}
+					return Ok(false)
+				},
+			};
+			if k_last.is_zero() {
+				return Ok(true)
+			}
+			let k = Self::to_u256(&reserves.0) * Self::to_u256(&reserves.1);
+			let total_supply = Self::to_u256(&token::Pallet::<T>::total_supply(lpt));
+			let liquidity = math::protocol_fee_liquidity(total_supply, k, k_last, T::ProtocolFeeDivisor::get());
+			let liquidity = T::Balance::unique_saturated_from(liquidity.min(U256::from(u128::MAX)).as_u128());
+			if liquidity > Zero::zero() {
+				token::Pallet::<T>::mint_from_system(lpt, &fee_to, &liquidity)?;
+			}
+			Ok(true)
+		}
+
+		fn _update_k_last(lpt: &T::AssetId, reserves: &(T::Balance, T::Balance)) {
+			<KLast<T>>::insert(lpt, Self::to_u256(&reserves.0) * Self::to_u256(&reserves.1));
+		}
+
+		/// Swap fee of pool `lpt` in basis points.
+		pub fn pool_fee(lpt: T::AssetId) -> u32 {
+			<PoolFees<T>>::get(lpt).unwrap_or_else(T::DefaultFee::get)
+		}
+
 		pub fn to_u256(value: &T::Balance) -> U256 {
 			U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(*value))
 		}
 
+		// Output received for `amount_in` after a fee of `fee` basis points.
 		pub fn _get_amount_out(
 			amount_in: &T::Balance,
 			reserve_in: &T::Balance,
 			reserve_out: &T::Balance,
+			fee: u32,
 		) -> T::Balance {
-			let amount_in_256 = Self::to_u256(amount_in);
-			let reserve_in_256 = Self::to_u256(reserve_in);
-			let reserve_out_256 = Self::to_u256(reserve_out);
-			let amount_in_with_fee = amount_in_256
-				.checked_mul(U256::from(997))
-				.expect("Multiplication overflow");
-			let numerator = amount_in_with_fee
-				.checked_mul(reserve_out_256)
-				.expect("Multiplication overflow");
-			let denominator = reserve_in_256
-				.checked_mul(U256::from(1000))
-				.expect("Multiplication overflow")
-				.checked_add(amount_in_with_fee)
-				.expect("Overflow");
-			T::Balance::unique_saturated_from(numerator.checked_div(denominator).expect("divided by zero").as_u128())
+			let amount_out = math::get_amount_out(Self::to_u256(amount_in), Self::to_u256(reserve_in), Self::to_u256(reserve_out), fee);
+			T::Balance::unique_saturated_from(amount_out.as_u128())
+		}
+
+		// Input needed to receive `amount_out` after a fee of `fee` basis points, rounded up in
+		// favour of the pool. `amount_out` must be below `reserve_out`.
+		pub fn _get_amount_in(
+			amount_out: &T::Balance,
+			reserve_in: &T::Balance,
+			reserve_out: &T::Balance,
+			fee: u32,
+		) -> T::Balance {
+			let amount_in = math::get_amount_in(Self::to_u256(amount_out), Self::to_u256(reserve_in), Self::to_u256(reserve_out), fee);
+			T::Balance::unique_saturated_from(amount_in.min(U256::from(u128::MAX)).as_u128())
+		}
+	}
+
+	impl<T: Config> token::DestroyGuard<T::AssetId> for Pallet<T> {
+		// Neither lp tokens nor the assets of a pool can be destroyed while that pool still holds
+		// reserves, as destroying them would burn what the pallet account holds
+		fn can_destroy(id: &T::AssetId) -> bool {
+			let is_empty = |lpt: &T::AssetId| {
+				let reserves = Self::reserves(lpt);
+				reserves.0.is_zero() && reserves.1.is_zero()
+			};
+			if <Rewards<T>>::contains_key(id) && !is_empty(id) {
+				return false
+			}
+			<PoolsOf<T>>::iter_key_prefix(id).all(|lpt| is_empty(&lpt))
 		}
 	}
 }
@@ -1,4 +1,5 @@
-use crate::Config;
+use crate::{Config, FEE_DENOMINATOR};
+use sp_core::U256;
 // use crate::balances;
 pub fn sqrt<T: Config> (y: T::Balance) -> T::Balance {
     if y > T::Balance::from(3u32) {
@@ -40,3 +41,106 @@ pub fn absdiff<T: Config>(
     z
 }
 
+// Output received for `amount_in` after a fee of `fee` basis points.
+pub fn get_amount_out(amount_in: U256, reserve_in: U256, reserve_out: U256, fee: u32) -> U256 {
+    let amount_in_with_fee = amount_in
+        .checked_mul(U256::from(FEE_DENOMINATOR.saturating_sub(fee)))
+        .expect("Multiplication overflow");
+    let numerator = amount_in_with_fee
+        .checked_mul(reserve_out)
+        .expect("Multiplication overflow");
+    let denominator = reserve_in
+        .checked_mul(U256::from(FEE_DENOMINATOR))
+        .expect("Multiplication overflow")
+        .checked_add(amount_in_with_fee)
+        .expect("Overflow");
+    numerator.checked_div(denominator).expect("divided by zero")
+}
+
+// Input needed to receive `amount_out` after a fee of `fee` basis points, rounded up in favour
+// of the pool. `amount_out` must be below `reserve_out`.
+pub fn get_amount_in(amount_out: U256, reserve_in: U256, reserve_out: U256, fee: u32) -> U256 {
+    let numerator = reserve_in
+        .checked_mul(amount_out)
+        .expect("Multiplication overflow")
+        .checked_mul(U256::from(FEE_DENOMINATOR))
+        .expect("Multiplication overflow");
+    let denominator = reserve_out
+        .checked_sub(amount_out)
+        .expect("Insufficient liquidity")
+        .checked_mul(U256::from(FEE_DENOMINATOR.saturating_sub(fee)))
+        .expect("Multiplication overflow");
+    numerator.checked_div(denominator).expect("divided by zero") + U256::one()
+}
+
+// Lp tokens minting `1 / divisor` of the growth of sqrt(k) since `k_last` out of `total_supply`,
+// as in Uniswap v2. Zero when the pool has not grown.
+pub fn protocol_fee_liquidity(total_supply: U256, k: U256, k_last: U256, divisor: u32) -> U256 {
+    let root_k = k.integer_sqrt();
+    let root_k_last = k_last.integer_sqrt();
+    if root_k <= root_k_last {
+        return U256::zero()
+    }
+    let numerator = total_supply * (root_k - root_k_last);
+    let denominator = root_k * U256::from(divisor.saturating_sub(1)) + root_k_last;
+    numerator / denominator
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_amount_in_buys_at_least_amount_out() {
+        let pools = [(1_000u128, 1_000u128), (10_000, 37), (37, 10_000), (1 << 40, 3 << 60)];
+        for &(reserve_in, reserve_out) in &pools {
+            let amounts_out = [1u128, 2, 7, reserve_out / 3, reserve_out / 2, reserve_out - 1];
+            let (reserve_in, reserve_out) = (U256::from(reserve_in), U256::from(reserve_out));
+            for &fee in &[0u32, 5, 30, 100] {
+                for &amount_out in &amounts_out {
+                    let amount_out = U256::from(amount_out);
+                    let amount_in = get_amount_in(amount_out, reserve_in, reserve_out, fee);
+                    // Rounding up never sells `amount_out` for less than it is worth
+                    assert!(get_amount_out(amount_in, reserve_in, reserve_out, fee) >= amount_out);
+                    // and overcharges by at most a single unit
+                    if amount_in > U256::one() {
+                        let short = get_amount_out(amount_in - 2, reserve_in, reserve_out, fee);
+                        assert!(short < amount_out);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn protocol_fee_liquidity_is_a_sixth_of_growth() {
+        // sqrt(k) grew from 1_000 to 1_100 on 1_000 lp tokens: 1_000 * 100 / (5 * 1_100 + 1_000)
+        let liquidity = protocol_fee_liquidity(
+            U256::from(1_000),
+            U256::from(1_100u64 * 1_100),
+            U256::from(1_000u64 * 1_000),
+            6,
+        );
+        assert_eq!(liquidity, U256::from(15));
+
+        // The minted share is worth a sixth of the growth, up to rounding down
+        let (total_supply, root_k, root_k_last) =
+            (10u128.pow(18), 3u128 * 10u128.pow(15), 2u128 * 10u128.pow(15));
+        let liquidity = protocol_fee_liquidity(
+            U256::from(total_supply),
+            U256::from(root_k) * U256::from(root_k),
+            U256::from(root_k_last) * U256::from(root_k_last),
+            6,
+        );
+        let share = liquidity * U256::from(root_k) / (U256::from(total_supply) + liquidity);
+        let expected = U256::from((root_k - root_k_last) / 6);
+        assert!(share <= expected && expected - share <= U256::from(1));
+    }
+
+    #[test]
+    fn protocol_fee_liquidity_is_zero_without_growth() {
+        let k = U256::from(1_000u64 * 1_000);
+        assert_eq!(protocol_fee_liquidity(U256::from(1_000), k, k, 6), U256::zero());
+        assert_eq!(protocol_fee_liquidity(U256::from(1_000), k, k + 1, 6), U256::zero());
+    }
+}
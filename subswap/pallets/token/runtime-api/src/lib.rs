@@ -0,0 +1,24 @@
+//! Runtime API definition for the token pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	pub trait TokenApi<AccountId, AssetId, Balance>
+	where
+		AccountId: Codec,
+		AssetId: Codec,
+		Balance: Codec,
+	{
+		/// Every asset held by `who` with its balance, starting with the native currency.
+		fn account_assets(who: AccountId) -> Vec<(AssetId, Balance)>;
+
+		/// The balance of `who` in `asset` when `snapshot` was taken, `None` if it was not.
+		fn balance_at(asset: AssetId, who: AccountId, snapshot: u32) -> Option<Balance>;
+
+		/// The total supply of `asset` when `snapshot` was taken, `None` if it was not.
+		fn total_supply_at(asset: AssetId, snapshot: u32) -> Option<Balance>;
+	}
+}
@@ -0,0 +1,188 @@
+//! Implementations of the `fungibles` traits for pallet_token. Asset 0 is routed to the native
+//! `Currency`.
+use super::*;
+use frame_support::{
+	ensure,
+	traits::{
+		tokens::{fungibles, DepositConsequence, WithdrawConsequence},
+		Currency, ExistenceRequirement,
+	},
+};
+use sp_runtime::{
+	traits::{CheckedAdd, CheckedSub, Saturating, Zero},
+	DispatchError, DispatchResult,
+};
+
+impl<T: Config> fungibles::Inspect<<T as frame_system::Config>::AccountId> for Pallet<T> {
+	type AssetId = T::AssetId;
+	type Balance = T::Balance;
+
+	fn total_issuance(asset: Self::AssetId) -> Self::Balance {
+		if asset.is_zero() {
+			return T::Currency::total_issuance()
+		}
+		Pallet::<T>::total_supply(asset)
+	}
+
+	fn minimum_balance(asset: Self::AssetId) -> Self::Balance {
+		if asset.is_zero() {
+			return T::Currency::minimum_balance()
+		}
+		Pallet::<T>::asset(asset).map(|details| details.min_balance).unwrap_or_default()
+	}
+
+	fn balance(asset: Self::AssetId, who: &T::AccountId) -> Self::Balance {
+		if asset.is_zero() {
+			return T::Currency::free_balance(who)
+		}
+		Balances::<T>::get(asset, who)
+	}
+
+	fn reducible_balance(asset: Self::AssetId, who: &T::AccountId, keep_alive: bool) -> Self::Balance {
+		if asset.is_zero() {
+			let free = T::Currency::free_balance(who);
+			return match keep_alive {
+				true => free.saturating_sub(T::Currency::minimum_balance()),
+				false => free,
+			}
+		}
+		if Pallet::<T>::ensure_not_frozen(asset, who).is_err() {
+			return Zero::zero()
+		}
+		Balances::<T>::get(asset, who)
+			.saturating_sub(Pallet::<T>::vesting_lock(asset, who))
+			.saturating_sub(Pallet::<T>::total_on_hold(asset, who))
+	}
+
+	fn can_deposit(
+		asset: Self::AssetId,
+		who: &T::AccountId,
+		amount: Self::Balance,
+		mint: bool,
+	) -> DepositConsequence {
+		if asset.is_zero() {
+			if mint && T::Currency::total_issuance().checked_add(&amount).is_none() {
+				return DepositConsequence::Overflow
+			}
+			if T::Currency::total_balance(who).saturating_add(amount) < T::Currency::minimum_balance() {
+				return DepositConsequence::BelowMinimum
+			}
+			return DepositConsequence::Success
+		}
+		if !Pallet::<T>::asset_exists(asset) {
+			return DepositConsequence::UnknownAsset
+		}
+		if mint && Pallet::<T>::total_supply(asset).checked_add(&amount).is_none() {
+			return DepositConsequence::Overflow
+		}
+		match Balances::<T>::get(asset, who).checked_add(&amount) {
+			None => return DepositConsequence::Overflow,
+			Some(balance) if balance < Self::minimum_balance(asset) => {
+				return DepositConsequence::BelowMinimum
+			},
+			Some(_) => (),
+		}
+		DepositConsequence::Success
+	}
+
+	fn can_withdraw(
+		asset: Self::AssetId,
+		who: &T::AccountId,
+		amount: Self::Balance,
+	) -> WithdrawConsequence<Self::Balance> {
+		if asset.is_zero() {
+			let remaining = match T::Currency::free_balance(who).checked_sub(&amount) {
+				Some(remaining) => remaining,
+				None => return WithdrawConsequence::NoFunds,
+			};
+			if !remaining.is_zero() && remaining < T::Currency::minimum_balance() {
+				return WithdrawConsequence::ReducedToZero(remaining)
+			}
+			return WithdrawConsequence::Success
+		}
+		if !Pallet::<T>::asset_exists(asset) {
+			return WithdrawConsequence::UnknownAsset
+		}
+		if Pallet::<T>::ensure_not_frozen(asset, who).is_err() {
+			return WithdrawConsequence::Frozen
+		}
+		let remaining = match Balances::<T>::get(asset, who).checked_sub(&amount) {
+			Some(remaining) => remaining,
+			None => return WithdrawConsequence::NoFunds,
+		};
+		if remaining < Pallet::<T>::vesting_lock(asset, who).saturating_add(Pallet::<T>::total_on_hold(asset, who)) {
+			return WithdrawConsequence::Frozen
+		}
+		WithdrawConsequence::Success
+	}
+
+	fn asset_exists(asset: Self::AssetId) -> bool {
+		Pallet::<T>::asset_exists(asset)
+	}
+}
+
+impl<T: Config> fungibles::Mutate<<T as frame_system::Config>::AccountId> for Pallet<T> {
+	fn mint_into(asset: Self::AssetId, who: &T::AccountId, amount: Self::Balance) -> DispatchResult {
+		ensure!(Pallet::<T>::asset_exists(asset), Error::<T>::Unknown);
+		Pallet::<T>::mint_from_system(&asset, who, &amount)
+	}
+
+	fn burn_from(
+		asset: Self::AssetId,
+		who: &T::AccountId,
+		amount: Self::Balance,
+	) -> Result<Self::Balance, DispatchError> {
+		Pallet::<T>::burn_from_system(&asset, who, &amount)?;
+		Ok(amount)
+	}
+}
+
+impl<T: Config> fungibles::Transfer<<T as frame_system::Config>::AccountId> for Pallet<T> {
+	fn transfer(
+		asset: Self::AssetId,
+		source: &T::AccountId,
+		dest: &T::AccountId,
+		amount: Self::Balance,
+		keep_alive: bool,
+	) -> Result<Self::Balance, DispatchError> {
+		if asset.is_zero() {
+			let existence = match keep_alive {
+				true => ExistenceRequirement::KeepAlive,
+				false => ExistenceRequirement::AllowDeath,
+			};
+			T::Currency::transfer(source, dest, amount, existence)?;
+			return Ok(amount)
+		}
+		Pallet::<T>::transfer_system(&asset, source, dest, &amount)?;
+		Ok(amount)
+	}
+}
+
+impl<T: Config> fungibles::Create<<T as frame_system::Config>::AccountId> for Pallet<T> {
+	fn create(
+		id: Self::AssetId,
+		admin: T::AccountId,
+		_is_sufficient: bool,
+		min_balance: Self::Balance,
+	) -> DispatchResult {
+		Pallet::<T>::do_create(id, admin, min_balance)
+	}
+}
+
+impl<T: Config> fungibles::Destroy<<T as frame_system::Config>::AccountId> for Pallet<T> {
+	fn start_destroy(id: Self::AssetId, maybe_check_owner: Option<T::AccountId>) -> DispatchResult {
+		Pallet::<T>::do_start_destroy(id, maybe_check_owner)
+	}
+
+	fn destroy_accounts(id: Self::AssetId, max_items: u32) -> Result<u32, DispatchError> {
+		Pallet::<T>::do_destroy_accounts(id, max_items)
+	}
+
+	fn destroy_approvals(id: Self::AssetId, max_items: u32) -> Result<u32, DispatchError> {
+		Pallet::<T>::do_destroy_approvals(id, max_items)
+	}
+
+	fn finish_destroy(id: Self::AssetId) -> DispatchResult {
+		Pallet::<T>::do_finish_destroy(id)
+	}
+}
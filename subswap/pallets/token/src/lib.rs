@@ -1,16 +1,182 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub use pallet::*;
+mod impl_fungibles;
 
 
 #[frame_support::pallet]
 pub mod pallet {
-	use frame_support::{pallet_prelude::{*, OptionQuery, ValueQuery},PalletId,{traits::Currency}};
+	use frame_support::{pallet_prelude::{*, OptionQuery, ValueQuery},PalletId,transactional,traits::{Currency,ReservableCurrency,BalanceStatus,ExistenceRequirement,WithdrawReasons,Imbalance}};
 	use frame_system::{pallet_prelude::{*, OriginFor},weights::WeightInfo};
-	use sp_runtime::traits::{AtLeast32Bit,AccountIdConversion,Zero,One,StaticLookup};
+	use sp_runtime::traits::{AtLeast32Bit,AccountIdConversion,MaybeSerializeDeserialize,Zero,One,StaticLookup,CheckedAdd,CheckedSub,CheckedMul,Saturating,UniqueSaturatedInto,Verify,IdentifyAccount};
 	use sp_core::Get;
+	use sp_std::vec::Vec;
 	use pallet_balances;
+
+	/// Domain tag prefixed to every message signed for a `permit`.
+	pub const PERMIT_CONTEXT: &[u8] = b"pallet_token/permit";
+
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, Default, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct AssetMetadata<Balance, BoundedString> {
+		/// The native balance reserved from the owner for storing this metadata
+		pub deposit: Balance,
+		/// The user friendly name of this asset
+		pub name: BoundedString,
+		/// The ticker symbol for this asset
+		pub symbol: BoundedString,
+		/// The number of decimals this asset uses to represent one unit
+		pub decimals: u8,
+	}
 	
+	#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum AssetStatus {
+		/// The asset can be minted, moved and burned
+		Live,
+		/// The asset is being torn down and its holders are removed in batches
+		Destroying,
+	}
+
+	/// Lets other pallets keep an asset alive while they depend on it, e.g. lp tokens backing a
+	/// market pool.
+	pub trait DestroyGuard<AssetId> {
+		fn can_destroy(id: &AssetId) -> bool;
+	}
+
+	impl<AssetId> DestroyGuard<AssetId> for () {
+		fn can_destroy(_id: &AssetId) -> bool {
+			true
+		}
+	}
+
+	/// Notified before `amount` of asset `id` moves from `from` to `to`. An error vetoes the
+	/// transfer.
+	pub trait OnTokenTransfer<AssetId, AccountId, Balance> {
+		fn on_transfer(
+			id: &AssetId,
+			from: &AccountId,
+			to: &AccountId,
+			amount: &Balance,
+		) -> DispatchResult;
+	}
+
+	#[impl_trait_for_tuples::impl_for_tuples(30)]
+	impl<AssetId, AccountId, Balance> OnTokenTransfer<AssetId, AccountId, Balance> for Tuple {
+		fn on_transfer(
+			id: &AssetId,
+			from: &AccountId,
+			to: &AccountId,
+			amount: &Balance,
+		) -> DispatchResult {
+			for_tuples!( #( Tuple::on_transfer(id, from, to, amount)?; )* );
+			Ok(())
+		}
+	}
+
+	/// Notified before `amount` of asset `id` is minted to `who`. An error vetoes the mint.
+	pub trait OnTokenMint<AssetId, AccountId, Balance> {
+		fn on_mint(id: &AssetId, who: &AccountId, amount: &Balance) -> DispatchResult;
+	}
+
+	#[impl_trait_for_tuples::impl_for_tuples(30)]
+	impl<AssetId, AccountId, Balance> OnTokenMint<AssetId, AccountId, Balance> for Tuple {
+		fn on_mint(id: &AssetId, who: &AccountId, amount: &Balance) -> DispatchResult {
+			for_tuples!( #( Tuple::on_mint(id, who, amount)?; )* );
+			Ok(())
+		}
+	}
+
+	/// Notified before `amount` of asset `id` is burned from `who`. An error vetoes the burn.
+	pub trait OnTokenBurn<AssetId, AccountId, Balance> {
+		fn on_burn(id: &AssetId, who: &AccountId, amount: &Balance) -> DispatchResult;
+	}
+
+	#[impl_trait_for_tuples::impl_for_tuples(30)]
+	impl<AssetId, AccountId, Balance> OnTokenBurn<AssetId, AccountId, Balance> for Tuple {
+		fn on_burn(id: &AssetId, who: &AccountId, amount: &Balance) -> DispatchResult {
+			for_tuples!( #( Tuple::on_burn(id, who, amount)?; )* );
+			Ok(())
+		}
+	}
+
+	/// Why part of a balance is held. Held balance stays with its owner but cannot be spent.
+	#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub enum HoldReason {
+		/// Collateral backing a vault
+		VaultCollateral,
+		/// Funds committed to an open order
+		PendingOrder,
+	}
+
+	/// Linear release of `per_period` every `period` blocks from `start`, `period_count` times.
+	#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct VestingSchedule<BlockNumber, Balance> {
+		/// The block from which periods are counted
+		pub start: BlockNumber,
+		/// The number of blocks in one period
+		pub period: BlockNumber,
+		/// The amount released at the end of each period
+		pub per_period: Balance,
+		/// The number of periods until everything is released
+		pub period_count: u32,
+	}
+
+	impl<BlockNumber: AtLeast32Bit + Copy, Balance: AtLeast32Bit + Copy> VestingSchedule<BlockNumber, Balance> {
+		/// The total amount vested by this schedule, `None` on overflow.
+		pub fn total_amount(&self) -> Option<Balance> {
+			self.per_period.checked_mul(&self.period_count.into())
+		}
+
+		/// The amount still locked at block `now`.
+		pub fn locked_amount(&self, now: BlockNumber) -> Balance {
+			let elapsed = now.saturating_sub(self.start) / self.period;
+			let periods: u32 = elapsed.unique_saturated_into();
+			let remaining = self.period_count.saturating_sub(periods);
+			self.per_period.saturating_mul(remaining.into())
+		}
+	}
+
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+	pub struct AssetDetails<AccountId, Balance> {
+		/// Can change the team and transfer ownership of the asset
+		pub owner: AccountId,
+		/// Can mint new supply
+		pub issuer: AccountId,
+		/// Can burn from any holder
+		pub admin: AccountId,
+		/// Can freeze holders
+		pub freezer: AccountId,
+		/// Issued by the system (e.g. lp tokens), so no signed account may mint it
+		pub is_system: bool,
+		/// Transfers and burns of every holder are blocked
+		pub is_frozen: bool,
+		/// Only whitelisted accounts may receive the asset
+		pub is_permissioned: bool,
+		/// Whether the asset is live or being destroyed
+		pub status: AssetStatus,
+		/// The native balance reserved from the owner for issuing the asset
+		pub deposit: Balance,
+		/// The smallest non-zero balance a holder may keep
+		pub min_balance: Balance,
+	}
+
+	impl<AccountId: Clone, Balance: Zero> AssetDetails<AccountId, Balance> {
+		/// Details of a live asset where `owner` holds every role.
+		pub fn new(owner: AccountId, is_system: bool, deposit: Balance) -> Self {
+			Self {
+				issuer: owner.clone(),
+				admin: owner.clone(),
+				freezer: owner.clone(),
+				owner,
+				is_system,
+				is_frozen: false,
+				is_permissioned: false,
+				status: AssetStatus::Live,
+				deposit,
+				min_balance: Zero::zero(),
+			}
+		}
+	}
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
 	pub struct Pallet<T>(_);
@@ -20,18 +186,73 @@ pub mod pallet {
 		/// Because this pallet emits events, it depends on the runtime's definition of an event.
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 		type PalletId: Get<PalletId>;
-		type AssetId: Parameter + AtLeast32Bit + Default + Copy + MaxEncodedLen;
+		type AssetId: Parameter + AtLeast32Bit + Default + Copy + MaxEncodedLen + MaybeSerializeDeserialize;
 		type WeightInfo: WeightInfo;
 
-		type Currency: Currency<Self::AccountId>;
+		/// The native currency, used for asset id 0 and for issuance deposits.
+		type Currency: ReservableCurrency<Self::AccountId, Balance = Self::Balance>;
+
+		/// The native balance reserved from the issuer of a new asset, returned once the asset
+		/// is destroyed.
+		#[pallet::constant]
+		type AssetDeposit: Get<Self::Balance>;
+
+		/// The native balance reserved for setting the metadata of an asset.
+		#[pallet::constant]
+		type MetadataDepositBase: Get<Self::Balance>;
+
+		/// The additional native balance reserved per byte of metadata name and symbol.
+		#[pallet::constant]
+		type MetadataDepositPerByte: Get<Self::Balance>;
+
+		/// The maximum length of an asset's name or symbol stored on-chain.
+		#[pallet::constant]
+		type StringLimit: Get<u32>;
+
+		/// The maximum number of holders or allowances removed by one `destroy_accounts` or
+		/// `destroy_approvals` call.
+		#[pallet::constant]
+		type RemoveItemsLimit: Get<u32>;
+
+		/// Decides whether an asset may be destroyed, e.g. the market keeps lp tokens of live
+		/// pools.
+		type DestroyGuard: DestroyGuard<Self::AssetId>;
+
+		/// The maximum number of legs in one `transfer_batch` or `transfer_multi` call.
+		#[pallet::constant]
+		type MaxBatchSize: Get<u32>;
+
+		/// The signature an owner produces off-chain to grant a `permit`.
+		type OffchainSignature: Verify<Signer = Self::OffchainPublic> + Parameter;
+
+		/// The public key behind `OffchainSignature`, identifying the owner account.
+		type OffchainPublic: IdentifyAccount<AccountId = Self::AccountId>;
+
+		/// The maximum number of vesting schedules an account may have per asset.
+		#[pallet::constant]
+		type MaxVestingSchedules: Get<u32>;
+
+		/// The maximum number of snapshots that can be taken of an asset.
+		#[pallet::constant]
+		type MaxSnapshots: Get<u32>;
+
+		/// Handlers called on every transfer, including the `*_system` helpers.
+		type OnTransfer: OnTokenTransfer<Self::AssetId, Self::AccountId, Self::Balance>;
+
+		/// Handlers called on every mint, including `mint_from_system` and issuing.
+		type OnMint: OnTokenMint<Self::AssetId, Self::AccountId, Self::Balance>;
+
+		/// Handlers called on every burn, including `burn_from_system`.
+		type OnBurn: OnTokenBurn<Self::AssetId, Self::AccountId, Self::Balance>;
 	}
 	// pub type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
 	#[pallet::storage]
-	pub(super) type Balances<T: Config> = StorageMap<
-		_, 
-		Blake2_128Concat, (T::AssetId, T::AccountId), 
-		T::Balance, 
+	pub(super) type Balances<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat, T::AssetId,
+		Blake2_128Concat, T::AccountId,
+		T::Balance,
 		ValueQuery
 	>;
 
@@ -48,15 +269,214 @@ pub mod pallet {
 		ValueQuery
 	>;
 
+	/// Optional cap on the total supply of an asset. It can be lowered but never raised.
 	#[pallet::storage]
-	#[pallet::getter(fn creator)]
-	pub(super) type Creator<T: Config> = StorageMap<
-		_, 
-		Blake2_128Concat, T::AssetId, 
-		T::AccountId, 
+	#[pallet::getter(fn max_supply)]
+	pub(super) type MaxSupply<T: Config> = StorageMap<
+		_,
+		Twox64Concat, T::AssetId,
+		T::Balance,
+		OptionQuery
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn asset)]
+	pub(super) type Asset<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat, T::AssetId,
+		AssetDetails<T::AccountId, T::Balance>,
+		OptionQuery
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn metadata)]
+	pub type Metadata<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat, T::AssetId,
+		AssetMetadata<T::Balance, BoundedVec<u8, T::StringLimit>>,
+		OptionQuery
+	>;
+
+	/// Index of the assets held by an account, kept in sync with `Balances`. \[who, asset_id]
+	#[pallet::storage]
+	pub(super) type AccountAssets<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat, T::AccountId,
+		Blake2_128Concat, T::AssetId,
+		(),
 		OptionQuery
 	>;
 
+	/// Holders whose balance of an asset is frozen. \[asset_id, who]
+	#[pallet::storage]
+	#[pallet::getter(fn is_frozen)]
+	pub(super) type FrozenAccounts<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat, T::AssetId,
+		Blake2_128Concat, T::AccountId,
+		bool,
+		ValueQuery
+	>;
+
+	/// Amount of an asset a spender may move on the owner's behalf. \[asset_id, owner, spender]
+	#[pallet::storage]
+	#[pallet::getter(fn allowance)]
+	pub(super) type Allowances<T: Config> = StorageNMap<
+		_,
+		(
+			NMapKey<Blake2_128Concat, T::AssetId>,
+			NMapKey<Blake2_128Concat, T::AccountId>,
+			NMapKey<Blake2_128Concat, T::AccountId>,
+		),
+		T::Balance,
+		ValueQuery
+	>;
+
+	/// Accounts allowed to receive a permissioned asset. \[asset_id, who]
+	#[pallet::storage]
+	#[pallet::getter(fn is_whitelisted)]
+	pub(super) type Whitelist<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat, T::AssetId,
+		Blake2_128Concat, T::AccountId,
+		bool,
+		ValueQuery
+	>;
+
+	/// Next nonce an owner must sign into a `permit`. \[owner]
+	#[pallet::storage]
+	#[pallet::getter(fn permit_nonce)]
+	pub(super) type PermitNonces<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat, T::AccountId,
+		T::Index,
+		ValueQuery
+	>;
+
+	/// Vesting schedules of a holder. \[asset_id, who]
+	#[pallet::storage]
+	#[pallet::getter(fn vesting_schedules)]
+	pub(super) type VestingSchedules<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat, T::AssetId,
+		Blake2_128Concat, T::AccountId,
+		BoundedVec<VestingSchedule<T::BlockNumber, T::Balance>, T::MaxVestingSchedules>,
+		ValueQuery
+	>;
+
+	/// Amount of a holder's balance that cannot be moved until it is claimed. \[asset_id, who]
+	#[pallet::storage]
+	#[pallet::getter(fn vesting_lock)]
+	pub(super) type VestingLocks<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat, T::AssetId,
+		Blake2_128Concat, T::AccountId,
+		T::Balance,
+		ValueQuery
+	>;
+
+	/// Balance held from a holder for a reason. \[asset_id, who, reason]
+	#[pallet::storage]
+	#[pallet::getter(fn balance_on_hold)]
+	pub(super) type Holds<T: Config> = StorageNMap<
+		_,
+		(
+			NMapKey<Blake2_128Concat, T::AssetId>,
+			NMapKey<Blake2_128Concat, T::AccountId>,
+			NMapKey<Blake2_128Concat, HoldReason>,
+		),
+		T::Balance,
+		ValueQuery
+	>;
+
+	/// Total balance held from a holder across all reasons. \[asset_id, who]
+	#[pallet::storage]
+	#[pallet::getter(fn total_on_hold)]
+	pub(super) type TotalHeld<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat, T::AssetId,
+		Blake2_128Concat, T::AccountId,
+		T::Balance,
+		ValueQuery
+	>;
+
+	/// Latest snapshot taken of an asset, 0 if none. \[asset_id]
+	#[pallet::storage]
+	#[pallet::getter(fn current_snapshot)]
+	pub(super) type CurrentSnapshot<T: Config> = StorageMap<
+		_,
+		Twox64Concat, T::AssetId,
+		u32,
+		ValueQuery
+	>;
+
+	/// Balances a holder had when snapshots were taken, each recorded on its first change after
+	/// the snapshot, sorted by snapshot. \[asset_id, who]
+	#[pallet::storage]
+	pub(super) type BalanceSnapshots<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat, T::AssetId,
+		Blake2_128Concat, T::AccountId,
+		BoundedVec<(u32, T::Balance), T::MaxSnapshots>,
+		ValueQuery
+	>;
+
+	/// Total supplies of an asset when snapshots were taken, each recorded on its first change
+	/// after the snapshot, sorted by snapshot. \[asset_id]
+	#[pallet::storage]
+	pub(super) type SupplySnapshots<T: Config> = StorageMap<
+		_,
+		Twox64Concat, T::AssetId,
+		BoundedVec<(u32, T::Balance), T::MaxSnapshots>,
+		ValueQuery
+	>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Genesis assets: id, creator, name, symbol, decimals
+		pub assets: Vec<(T::AssetId, T::AccountId, Vec<u8>, Vec<u8>, u8)>,
+		/// Genesis balances: id, account, balance
+		pub accounts: Vec<(T::AssetId, T::AccountId, T::Balance)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { assets: Default::default(), accounts: Default::default() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			// save 0 for native currency
+			let mut next_id: T::AssetId = One::one();
+			for (id, creator, name, symbol, decimals) in &self.assets {
+				assert!(!id.is_zero(), "Asset id 0 is reserved for the native currency");
+				assert!(!<Asset<T>>::contains_key(id), "Asset id already in use");
+
+				let name: BoundedVec<u8, T::StringLimit> =
+					name.clone().try_into().expect("asset name is too long");
+				let symbol: BoundedVec<u8, T::StringLimit> =
+					symbol.clone().try_into().expect("asset symbol is too long");
+
+				<Asset<T>>::insert(id, AssetDetails::new(creator.clone(), false, Zero::zero()));
+				<TotalSupply<T>>::insert(id, T::Balance::zero());
+				<Metadata<T>>::insert(id, AssetMetadata { deposit: Zero::zero(), name, symbol, decimals: *decimals });
+				if *id >= next_id {
+					next_id = *id + One::one();
+				}
+			}
+			<NextAssetId<T>>::put(next_id);
+
+			for (id, who, amount) in &self.accounts {
+				assert!(<Asset<T>>::contains_key(id), "Asset does not exist");
+				Pallet::<T>::write_balance(*id, who, <Balances<T>>::get(id, who) + *amount);
+				<TotalSupply<T>>::mutate(id, |supply| *supply += *amount);
+			}
+		}
+	}
+
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
@@ -72,8 +492,65 @@ pub mod pallet {
         Minted(T::AssetId, T::AccountId, T::Balance),
         /// Some assets were burned. \[asset_id, owner, balance]
         Burned(T::AssetId, T::AccountId, T::Balance),
-        /// Some assets were destroyed. \[asset_id, owner, balance\]
-		Destroyed(T::AssetId, T::AccountId, T::Balance),
+        /// The destruction of an asset was started. \[asset_id\]
+		DestructionStarted(T::AssetId),
+		/// Some holders of an asset being destroyed were removed. \[asset_id, removed\]
+		AccountsDestroyed(T::AssetId, u32),
+		/// Some allowances of an asset being destroyed were removed. \[asset_id, removed\]
+		ApprovalsDestroyed(T::AssetId, u32),
+		/// An asset was destroyed and all of its storage removed. \[asset_id\]
+		Destroyed(T::AssetId),
+		/// An allowance was set for a spender. \[asset_id, owner, spender, allowance\]
+		Approved(T::AssetId, T::AccountId, T::AccountId, T::Balance),
+		/// An allowance was cancelled. \[asset_id, owner, spender\]
+		ApprovalCancelled(T::AssetId, T::AccountId, T::AccountId),
+		/// Some assets were transferred by a spender on the owner's behalf.
+		/// \[asset_id, owner, spender, target, amount\]
+		TransferredApproved(T::AssetId, T::AccountId, T::AccountId, T::AccountId, T::Balance),
+		/// The management team of an asset changed. \[asset_id, issuer, admin, freezer\]
+		TeamChanged(T::AssetId, T::AccountId, T::AccountId, T::AccountId),
+		/// The owner of an asset changed. \[asset_id, owner\]
+		OwnerChanged(T::AssetId, T::AccountId),
+		/// A holder's balance of an asset was frozen. \[asset_id, who\]
+		Frozen(T::AssetId, T::AccountId),
+		/// A holder's balance of an asset was thawed. \[asset_id, who\]
+		Thawed(T::AssetId, T::AccountId),
+		/// An asset was frozen for every holder. \[asset_id\]
+		AssetFrozen(T::AssetId),
+		/// An asset was thawed for every holder. \[asset_id\]
+		AssetThawed(T::AssetId),
+		/// The supply cap of an asset was set. \[asset_id, max_supply, mintable\]
+		MaxSupplySet(T::AssetId, T::Balance, T::Balance),
+		/// The minimum balance of an asset was set. \[asset_id, min_balance\]
+		MinBalanceSet(T::AssetId, T::Balance),
+		/// A vesting schedule was added to a holder. \[asset_id, who, schedule\]
+		VestingScheduleAdded(T::AssetId, T::AccountId, VestingSchedule<T::BlockNumber, T::Balance>),
+		/// Vested balance was unlocked. \[asset_id, who, still_locked\]
+		Claimed(T::AssetId, T::AccountId, T::Balance),
+		/// An account was allowed to receive a permissioned asset. \[asset_id, who\]
+		Whitelisted(T::AssetId, T::AccountId),
+		/// An account may no longer receive a permissioned asset. \[asset_id, who\]
+		Unwhitelisted(T::AssetId, T::AccountId),
+		/// Balance was put on hold. \[asset_id, who, reason, amount\]
+		Held(T::AssetId, T::AccountId, HoldReason, T::Balance),
+		/// Held balance was released. \[asset_id, who, reason, amount\]
+		Released(T::AssetId, T::AccountId, HoldReason, T::Balance),
+		/// Held balance was burned. \[asset_id, who, reason, amount\]
+		Slashed(T::AssetId, T::AccountId, HoldReason, T::Balance),
+		/// Held balance was transferred. \[asset_id, from, to, reason, amount\]
+		TransferredOnHold(T::AssetId, T::AccountId, T::AccountId, HoldReason, T::Balance),
+		/// A snapshot of an asset was taken. \[asset_id, snapshot\]
+		SnapshotTaken(T::AssetId, u32),
+		/// Native balance was reserved as a deposit for an asset. \[asset_id, who, amount\]
+		DepositReserved(T::AssetId, T::AccountId, T::Balance),
+		/// A deposit for an asset was returned. \[asset_id, who, amount\]
+		DepositUnreserved(T::AssetId, T::AccountId, T::Balance),
+		/// Metadata of an asset was set. \[asset_id, name, symbol, decimals\]
+		MetadataSet(T::AssetId, Vec<u8>, Vec<u8>, u8),
+		/// Metadata of an asset was cleared. \[asset_id\]
+		MetadataCleared(T::AssetId),
+		/// An asset class was created with a given id. \[asset_id, owner\]
+		Created(T::AssetId, T::AccountId),
 	}
 
 	// Errors inform users that something went wrong.
@@ -85,41 +562,114 @@ pub mod pallet {
         BalanceLow,
         /// Balance should be non-zero
         BalanceZero,
-        /// Not the creator of the asset
+        /// Not the owner of the asset
         NotTheCreator,
         /// Not the approver for the account
         NotApproved,
         /// Created by System
 		CreatedBySystem,
+		/// The signing account does not hold the role required for this operation
+		NoPermission,
+		/// Allowance cannot be decreased below zero
+		AllowanceUnderflow,
+		/// An arithmetic operation on a balance or supply overflowed
+		Overflow,
+		/// Name or symbol is longer than `StringLimit`
+		BadMetadata,
+		/// The asset has no metadata
+		NoMetadata,
+		/// Unknown asset
+		Unknown,
+		/// The asset id is already taken, or the asset still has holders
+		InUse,
+		/// The asset is not being destroyed
+		LiveAsset,
+		/// The asset is being destroyed
+		AssetNotLive,
+		/// The asset is still in use elsewhere, e.g. an lp token backing a pool
+		CannotDestroy,
+		/// The asset or the holder's balance of it is frozen
+		Frozen,
+		/// Minting would exceed the supply cap of the asset
+		MaxSupplyExceeded,
+		/// The resulting balance would be non-zero but below the minimum balance of the asset
+		BelowMinimum,
+		/// The supply cap can only be lowered, and not below the current supply
+		InvalidMaxSupply,
+		/// The permit signature does not match the owner
+		BadSignature,
+		/// The permit deadline has passed
+		PermitExpired,
+		/// The vesting schedule releases nothing, or targets the native currency
+		InvalidSchedule,
+		/// The holder already has the maximum number of vesting schedules for the asset
+		TooManySchedules,
+		/// The amount is still locked by vesting
+		VestingLocked,
+		/// The snapshot has not been taken yet
+		UnknownSnapshot,
+		/// The asset already has the maximum number of snapshots
+		TooManySnapshots,
+		/// The asset is permissioned and the receiver is not whitelisted
+		NotWhitelisted,
+		/// The amount is on hold and cannot be spent
+		BalanceOnHold,
+		/// Less than the amount is held for the reason
+		InsufficientHold,
 	}
 
 	
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		/// Issue a new class of fungible assets. There are, and will only ever be, `total`
-		/// such assets and they'll all belong to the `origin` initially. It will have an
-		/// identifier `AssetId` instance: this will be specified in the `Issued` event.
-		
+		/// Issue a new class of fungible assets. `total` such assets are issued and they'll all
+		/// belong to the `origin` initially. When `max_supply` is given, minting can never take
+		/// the total supply above it. `AssetDeposit` is reserved from `origin` until the asset is
+		/// destroyed. A `permissioned` asset can only be received by accounts its admin
+		/// whitelists, starting with `origin`. It will have an identifier `AssetId` instance: this
+		/// will be specified in the `Issued` event.
 		#[pallet::call_index(0)]
 		#[pallet::weight(0)]
-		pub fn issue(origin: OriginFor<T>, total: T::Balance) -> DispatchResult {
+		pub fn issue(origin: OriginFor<T>,
+			total: T::Balance,
+			max_supply: Option<T::Balance>,
+			permissioned: bool
+		) -> DispatchResult {
 			let origin = ensure_signed(origin)?;
+			if let Some(max_supply) = max_supply {
+				ensure!(total <= max_supply, Error::<T>::MaxSupplyExceeded);
+			}
+			let deposit = T::AssetDeposit::get();
+			T::Currency::reserve(&origin, deposit)?;
 			// save 0 for native currency
 			let id = Self::next_asset_id();
 			<NextAssetId<T>>::mutate(|id| {
                 *id += One::one();
             });
 
-			<Balances<T>>::insert((id, &origin), total);
+			if !total.is_zero() {
+				T::OnMint::on_mint(&id, &origin, &total)?;
+			}
+			Self::write_balance(id, &origin, total);
 			<TotalSupply<T>>::insert(id, total);
-			<Creator<T>>::insert(id, &origin);
+			<Asset<T>>::insert(id, AssetDetails {
+				is_permissioned: permissioned,
+				..AssetDetails::new(origin.clone(), false, deposit)
+			});
+			if permissioned {
+				<Whitelist<T>>::insert(id, &origin, true);
+			}
 
+			Self::deposit_event(Event::DepositReserved(id, origin.clone(), deposit));
 			Self::deposit_event(Event::Issued(id, origin, total));
+			if let Some(max_supply) = max_supply {
+				<MaxSupply<T>>::insert(id, max_supply);
+				Self::deposit_event(Event::MaxSupplySet(id, max_supply, max_supply - total));
+			}
 			Ok(())
 		}
 		
 
-		/// Mint any assets of `id` owned by `origin`.
+		/// Mint assets of `id` to `target`. Only the issuer of the asset may do this.
 		#[pallet::call_index(1)]
 		#[pallet::weight(0)]
         pub fn mint(origin: OriginFor<T>,
@@ -129,17 +679,18 @@ pub mod pallet {
         )-> DispatchResult{
             let origin = ensure_signed(origin)?;
             let target = T::Lookup::lookup(target)?;
-            let creator = <Creator<T>>::get(id).unwrap();
-            ensure!(origin == creator, Error::<T>::NotTheCreator);
+            let details = Self::asset_details(id)?;
+            ensure!(!details.is_system, Error::<T>::CreatedBySystem);
+            ensure!(origin == details.issuer, Error::<T>::NoPermission);
             ensure!(!amount.is_zero(), Error::<T>::AmountZero);
 
-            <Balances<T>>::mutate((id, target.clone()), |balance| *balance += amount);
-            <TotalSupply<T>>::mutate(id, |supply| *supply += amount);
+            Self::do_mint(id, &target, amount)?;
             Self::deposit_event(Event::Minted(id, target, amount));
 			Ok(())
         }
 
-		/// Burn any assets of `id` owned by `origin`.
+		/// Burn assets of `id` held by `target`. Anyone may burn their own assets; burning from
+		/// another holder requires the admin role.
         #[pallet::call_index(3)]
 		#[pallet::weight(0)]
         pub fn burn(origin: OriginFor<T>,
@@ -148,14 +699,15 @@ pub mod pallet {
            	amount: T::Balance
        	)-> DispatchResult{
            let origin = ensure_signed(origin)?;
-           let origin_account = (id, origin.clone());
-           let origin_balance = <Balances<T>>::get(&origin_account);
+           let target = T::Lookup::lookup(target)?;
            ensure!(!amount.is_zero(), Error::<T>::AmountZero);
-           ensure!(origin_balance >= amount, Error::<T>::BalanceLow);
+           if target != origin {
+               let details = Self::asset_details(id)?;
+               ensure!(origin == details.admin, Error::<T>::NoPermission);
+           }
 
-           <Balances<T>>::insert(origin_account, origin_balance - amount);
-           <TotalSupply<T>>::mutate(id, |supply| *supply -= amount);
-           Self::deposit_event(Event::Burned(id, origin, amount));
+           Self::do_burn(id, &target, amount)?;
+           Self::deposit_event(Event::Burned(id, target, amount));
 		   Ok(())
        }
 
@@ -168,175 +720,1176 @@ pub mod pallet {
 			amount: T::Balance
 		) -> DispatchResult{
 			let origin = ensure_signed(origin)?;
-			let origin_account = (id, origin.clone());
-			let origin_balance = <Balances<T>>::get(&origin_account);
 			let target = T::Lookup::lookup(target)?;
 			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
-			ensure!(origin_balance >= amount, Error::<T>::BalanceLow);
 
-			Self::deposit_event(Event::Transferred(id, origin, target.clone(), amount));
-			<Balances<T>>::insert(origin_account, origin_balance - amount);
-			<Balances<T>>::mutate((id, target), |balance| *balance += amount);
+			Self::do_transfer(id, &origin, &target, amount)?;
+			Self::deposit_event(Event::Transferred(id, origin, target, amount));
 			Ok(())
 		}
 
-		/// Destroy any assets of `id` owned by `origin`.
-        #[pallet::call_index(5)]
+		/// Allow `spender` to move up to `amount` of asset `id` owned by `origin`, replacing any
+		/// previous allowance.
+		#[pallet::call_index(6)]
 		#[pallet::weight(0)]
-        pub fn destroy(origin: OriginFor<T>, id: T::AssetId) -> DispatchResult{
-			let origin = ensure_signed(origin)?;
-			let balance = <Balances<T>>::take((id, &origin));
-			ensure!(!balance.is_zero(), Error::<T>::BalanceZero);
+		pub fn approve(origin: OriginFor<T>,
+			id: T::AssetId,
+			spender: <<T as frame_system::Config>::Lookup as StaticLookup>::Source,
+			amount: T::Balance
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+			let spender = T::Lookup::lookup(spender)?;
 
-			<TotalSupply<T>>::mutate(id, |total_supply| *total_supply -= balance);
-			Self::deposit_event(Event::Destroyed(id, origin, balance));
+			<Allowances<T>>::insert((id, &owner, &spender), amount);
+			Self::deposit_event(Event::Approved(id, owner, spender, amount));
 			Ok(())
 		}
 
+		/// Raise the allowance of `spender` over asset `id` owned by `origin` by `amount`.
+		#[pallet::call_index(7)]
+		#[pallet::weight(0)]
+		pub fn increase_allowance(origin: OriginFor<T>,
+			id: T::AssetId,
+			spender: <<T as frame_system::Config>::Lookup as StaticLookup>::Source,
+			amount: T::Balance
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+			let spender = T::Lookup::lookup(spender)?;
+			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
 
-
-	}
-
-	impl<T: Config> Pallet<T> {
-		// Module account id
-		pub fn account_id() -> T::AccountId {
-			T::PalletId::get().into_account_truncating()
-		}
-
-		// Get the asset `id` balance of `who`.
-		pub fn balance(id: T::AssetId, who: T::AccountId) -> T::Balance {
-			if id == Zero::zero() {
-				return pallet_balances::Pallet::<T>::free_balance(&who);
-
-			}
-			<Balances<T>>::get((id, who))
+			let allowance = <Allowances<T>>::get((id, &owner, &spender))
+				.checked_add(&amount)
+				.ok_or(Error::<T>::Overflow)?;
+			<Allowances<T>>::insert((id, &owner, &spender), allowance);
+			Self::deposit_event(Event::Approved(id, owner, spender, allowance));
+			Ok(())
 		}
-		
-		// Get the total supply of an asset `id`.
-		// pub fn total_supply(id: T::AssetId) -> T::Balance {
-		// 	TotalSupply::<T>::get(id)
-		// }
 
-		pub fn mint_from_system(
-			id: &T::AssetId,
-			target: &T::AccountId,
-			amount: &T::Balance,
+		/// Lower the allowance of `spender` over asset `id` owned by `origin` by `amount`.
+		#[pallet::call_index(8)]
+		#[pallet::weight(0)]
+		pub fn decrease_allowance(origin: OriginFor<T>,
+			id: T::AssetId,
+			spender: <<T as frame_system::Config>::Lookup as StaticLookup>::Source,
+			amount: T::Balance
 		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+			let spender = T::Lookup::lookup(spender)?;
 			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
-			if *id == Zero::zero() {
-				let new_free = pallet_balances::Pallet::<T>::free_balance(target) + *amount;
-				pallet_balances::Pallet::<T>::mutate_account(target, |account| {
-					account.free = new_free;
-					account.free
-				});
+
+			let allowance = <Allowances<T>>::get((id, &owner, &spender));
+			ensure!(allowance >= amount, Error::<T>::AllowanceUnderflow);
+			let allowance = allowance - amount;
+			if allowance.is_zero() {
+				<Allowances<T>>::remove((id, &owner, &spender));
 			} else {
-				<Balances<T>>::mutate((*id, target.clone()), |balance| *balance += *amount);
-				<TotalSupply<T>>::mutate(*id, |supply| *supply += *amount);
+				<Allowances<T>>::insert((id, &owner, &spender), allowance);
 			}
-			Self::deposit_event(Event::Minted(*id, target.clone(), *amount));
+			Self::deposit_event(Event::Approved(id, owner, spender, allowance));
 			Ok(())
 		}
 
-		pub fn burn_from_system(
-			id: &T::AssetId,
-			target: &T::AccountId,
-			amount: &T::Balance,
+		/// Remove any allowance of `spender` over asset `id` owned by `origin`.
+		#[pallet::call_index(9)]
+		#[pallet::weight(0)]
+		pub fn cancel_approval(origin: OriginFor<T>,
+			id: T::AssetId,
+			spender: <<T as frame_system::Config>::Lookup as StaticLookup>::Source
 		) -> DispatchResult {
-			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
-			if *id == Zero::zero() {
-				let new_free = pallet_balances::Pallet::<T>::free_balance(target) - *amount;
-				let _free = pallet_balances::Pallet::<T>::mutate_account(target, |account| {
-					account.free = new_free;
-	
-					account.free
-				});
-			} else {
-				<Balances<T>>::mutate((*id, target.clone()), |balance| *balance -= *amount);
-				<TotalSupply<T>>::mutate(*id, |supply| *supply -= *amount);
-			}
-			Self::deposit_event(Event::Burned(*id, target.clone(), *amount));
+			let owner = ensure_signed(origin)?;
+			let spender = T::Lookup::lookup(spender)?;
+			ensure!(<Allowances<T>>::contains_key((id, &owner, &spender)), Error::<T>::NotApproved);
+
+			<Allowances<T>>::remove((id, &owner, &spender));
+			Self::deposit_event(Event::ApprovalCancelled(id, owner, spender));
 			Ok(())
 		}
 
-		pub fn transfer_system(
-			id: &T::AssetId,
-			source: &T::AccountId,
-			target: &T::AccountId,
-			amount: &T::Balance,
+		/// Move some assets of `owner` to `target` using the allowance granted to `origin`.
+		#[pallet::call_index(10)]
+		#[pallet::weight(0)]
+		pub fn transfer_from(origin: OriginFor<T>,
+			id: T::AssetId,
+			owner: <<T as frame_system::Config>::Lookup as StaticLookup>::Source,
+			target: <<T as frame_system::Config>::Lookup as StaticLookup>::Source,
+			amount: T::Balance
 		) -> DispatchResult {
+			let spender = ensure_signed(origin)?;
+			let owner = T::Lookup::lookup(owner)?;
+			let target = T::Lookup::lookup(target)?;
 			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
-			Self::deposit_event(Event::Transferred(*id, source.clone(), target.clone(), *amount));
-			if *id == Zero::zero() {
-				pallet_balances::Pallet::<T>::mutate_account(source, |account| {
-					account.free -= *amount;
-				});
-				pallet_balances::Pallet::<T>::mutate_account(target, |account| {
-					account.free += *amount;
-				});
+
+			let remaining = <Allowances<T>>::get((id, &owner, &spender))
+				.checked_sub(&amount)
+				.ok_or(Error::<T>::NotApproved)?;
+
+			Self::do_transfer(id, &owner, &target, amount)?;
+			if remaining.is_zero() {
+				<Allowances<T>>::remove((id, &owner, &spender));
 			} else {
-				<Balances<T>>::mutate((*id, target), |balance| *balance += *amount);
-				<Balances<T>>::mutate((*id, source), |balance| *balance -= *amount);
+				<Allowances<T>>::insert((id, &owner, &spender), remaining);
 			}
+			Self::deposit_event(Event::TransferredApproved(id, owner, spender, target, amount));
 			Ok(())
 		}
 
-		pub fn transfer_from_system(
-			id: &T::AssetId,
-			target: &T::AccountId,
-			amount: &T::Balance,
+		/// Set the name, symbol and decimals of asset `id`. A deposit of `MetadataDepositBase`
+		/// plus `MetadataDepositPerByte` for every byte of name and symbol is reserved from the
+		/// owner. Only the owner may do this.
+		#[pallet::call_index(11)]
+		#[pallet::weight(0)]
+		pub fn set_metadata(origin: OriginFor<T>,
+			id: T::AssetId,
+			name: Vec<u8>,
+			symbol: Vec<u8>,
+			decimals: u8
 		) -> DispatchResult {
-			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
-			let module_account = Self::account_id();
-			Self::deposit_event(Event::TransferredFromSystem(*id, target.clone(), *amount));
-			if *id == Zero::zero() {
-				pallet_balances::Pallet::<T>::mutate_account(&module_account, |account| {
-					account.free -= *amount;
-				});
-				pallet_balances::Pallet::<T>::mutate_account(target, |account| {
-					account.free += *amount;
-				});
-			} else {
-				<Balances<T>>::mutate((*id, target.clone()), |balance| *balance += *amount);
-				<Balances<T>>::mutate((*id, module_account), |balance| *balance -= *amount);
+			let origin = ensure_signed(origin)?;
+			let details = Self::asset_details(id)?;
+			ensure!(origin == details.owner, Error::<T>::NotTheCreator);
+
+			let bounded_name: BoundedVec<u8, T::StringLimit> =
+				name.clone().try_into().map_err(|_| Error::<T>::BadMetadata)?;
+			let bounded_symbol: BoundedVec<u8, T::StringLimit> =
+				symbol.clone().try_into().map_err(|_| Error::<T>::BadMetadata)?;
+
+			let deposit = T::MetadataDepositPerByte::get()
+				.saturating_mul(((name.len() + symbol.len()) as u32).into())
+				.saturating_add(T::MetadataDepositBase::get());
+			let old_deposit = Self::metadata(id).map(|metadata| metadata.deposit).unwrap_or_default();
+			if deposit > old_deposit {
+				T::Currency::reserve(&origin, deposit - old_deposit)?;
+				Self::deposit_event(Event::DepositReserved(id, origin.clone(), deposit - old_deposit));
+			} else if deposit < old_deposit {
+				T::Currency::unreserve(&origin, old_deposit - deposit);
+				Self::deposit_event(Event::DepositUnreserved(id, origin.clone(), old_deposit - deposit));
 			}
+
+			<Metadata<T>>::insert(id, AssetMetadata { deposit, name: bounded_name, symbol: bounded_symbol, decimals });
+			Self::deposit_event(Event::MetadataSet(id, name, symbol, decimals));
 			Ok(())
 		}
 
+		/// Remove the metadata of asset `id` and return its deposit. Only the owner may do this.
+		#[pallet::call_index(12)]
+		#[pallet::weight(0)]
+		pub fn clear_metadata(origin: OriginFor<T>, id: T::AssetId) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			let details = Self::asset_details(id)?;
+			ensure!(origin == details.owner, Error::<T>::NotTheCreator);
+			let metadata = <Metadata<T>>::take(id).ok_or(Error::<T>::NoMetadata)?;
 
-
-		pub fn transfer_to_system(
-			id: &T::AssetId,
-			source: &T::AccountId,
-			amount: &T::Balance,
-		) -> DispatchResult {
-			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
-			let module_account = Self::account_id();
-			Self::deposit_event(Event::TransferredToSystem(*id, source.clone(), *amount));
-			if *id == Zero::zero() {
-				pallet_balances::Pallet::<T>::mutate_account(source, |account| {
-					account.free -= *amount;
-				});
-				pallet_balances::Pallet::<T>::mutate_account(&module_account, |account| {
-					account.free += *amount;
-				});
-			} else {
-				<Balances<T>>::mutate((*id, source.clone()), |balance| *balance -= *amount);
-				<Balances<T>>::mutate((*id, module_account), |balance| *balance += *amount);
+			if !metadata.deposit.is_zero() {
+				T::Currency::unreserve(&origin, metadata.deposit);
+				Self::deposit_event(Event::DepositUnreserved(id, origin, metadata.deposit));
 			}
+			Self::deposit_event(Event::MetadataCleared(id));
 			Ok(())
 		}
 
-		pub fn issue_from_system(total: T::Balance) -> DispatchResult {
-			let id = Self::next_asset_id();
-			let module_account  = Self::account_id();
-			<NextAssetId<T>>::mutate(|id| {
-				*id += One::one();
-			});
-			<TotalSupply<T>>::insert(id, total);
-			<Balances<T>>::insert((id, module_account), total.clone()); 
-			Self::deposit_event(Event::IssuedBySystem(id, total));
+		/// Change the issuer, admin and freezer of asset `id`. Only the owner may do this.
+		#[pallet::call_index(13)]
+		#[pallet::weight(0)]
+		pub fn set_team(origin: OriginFor<T>,
+			id: T::AssetId,
+			issuer: <<T as frame_system::Config>::Lookup as StaticLookup>::Source,
+			admin: <<T as frame_system::Config>::Lookup as StaticLookup>::Source,
+			freezer: <<T as frame_system::Config>::Lookup as StaticLookup>::Source
+		) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			let issuer = T::Lookup::lookup(issuer)?;
+			let admin = T::Lookup::lookup(admin)?;
+			let freezer = T::Lookup::lookup(freezer)?;
+
+			<Asset<T>>::try_mutate(id, |maybe_details| -> DispatchResult {
+				let details = maybe_details.as_mut().ok_or(Error::<T>::Unknown)?;
+				ensure!(!details.is_system, Error::<T>::CreatedBySystem);
+				ensure!(origin == details.owner, Error::<T>::NotTheCreator);
+
+				details.issuer = issuer.clone();
+				details.admin = admin.clone();
+				details.freezer = freezer.clone();
+				Ok(())
+			})?;
+			Self::deposit_event(Event::TeamChanged(id, issuer, admin, freezer));
 			Ok(())
 		}
-	}
-}
 
+		/// Hand the ownership of asset `id` to `owner`, moving the reserved deposits along with
+		/// it. Only the current owner may do this.
+		#[pallet::call_index(14)]
+		#[pallet::weight(0)]
+		pub fn transfer_ownership(origin: OriginFor<T>,
+			id: T::AssetId,
+			owner: <<T as frame_system::Config>::Lookup as StaticLookup>::Source
+		) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			let owner = T::Lookup::lookup(owner)?;
+
+			<Asset<T>>::try_mutate(id, |maybe_details| -> DispatchResult {
+				let details = maybe_details.as_mut().ok_or(Error::<T>::Unknown)?;
+				ensure!(!details.is_system, Error::<T>::CreatedBySystem);
+				ensure!(origin == details.owner, Error::<T>::NotTheCreator);
+
+				let deposit = <Metadata<T>>::get(id)
+					.map(|metadata| metadata.deposit)
+					.unwrap_or_default()
+					.saturating_add(details.deposit);
+				T::Currency::repatriate_reserved(&origin, &owner, deposit, BalanceStatus::Reserved)?;
+
+				details.owner = owner.clone();
+				Ok(())
+			})?;
+			Self::deposit_event(Event::OwnerChanged(id, owner));
+			Ok(())
+		}
+
+		/// Stop `who` from moving or burning its balance of asset `id`. Only the freezer may do
+		/// this.
+		#[pallet::call_index(15)]
+		#[pallet::weight(0)]
+		pub fn freeze(origin: OriginFor<T>,
+			id: T::AssetId,
+			who: <<T as frame_system::Config>::Lookup as StaticLookup>::Source
+		) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			let who = T::Lookup::lookup(who)?;
+			let details = Self::asset_details(id)?;
+			ensure!(origin == details.freezer, Error::<T>::NoPermission);
+
+			<FrozenAccounts<T>>::insert(id, &who, true);
+			Self::deposit_event(Event::Frozen(id, who));
+			Ok(())
+		}
+
+		/// Allow `who` to move its balance of asset `id` again. Only the freezer may do this.
+		#[pallet::call_index(16)]
+		#[pallet::weight(0)]
+		pub fn thaw(origin: OriginFor<T>,
+			id: T::AssetId,
+			who: <<T as frame_system::Config>::Lookup as StaticLookup>::Source
+		) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			let who = T::Lookup::lookup(who)?;
+			let details = Self::asset_details(id)?;
+			ensure!(origin == details.freezer, Error::<T>::NoPermission);
+
+			<FrozenAccounts<T>>::remove(id, &who);
+			Self::deposit_event(Event::Thawed(id, who));
+			Ok(())
+		}
+
+		/// Stop every holder from moving or burning asset `id`. Only the freezer may do this.
+		#[pallet::call_index(17)]
+		#[pallet::weight(0)]
+		pub fn freeze_asset(origin: OriginFor<T>, id: T::AssetId) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+
+			<Asset<T>>::try_mutate(id, |maybe_details| -> DispatchResult {
+				let details = maybe_details.as_mut().ok_or(Error::<T>::Unknown)?;
+				ensure!(origin == details.freezer, Error::<T>::NoPermission);
+				details.is_frozen = true;
+				Ok(())
+			})?;
+			Self::deposit_event(Event::AssetFrozen(id));
+			Ok(())
+		}
+
+		/// Allow holders to move asset `id` again. Only the freezer may do this.
+		#[pallet::call_index(18)]
+		#[pallet::weight(0)]
+		pub fn thaw_asset(origin: OriginFor<T>, id: T::AssetId) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+
+			<Asset<T>>::try_mutate(id, |maybe_details| -> DispatchResult {
+				let details = maybe_details.as_mut().ok_or(Error::<T>::Unknown)?;
+				ensure!(origin == details.freezer, Error::<T>::NoPermission);
+				details.is_frozen = false;
+				Ok(())
+			})?;
+			Self::deposit_event(Event::AssetThawed(id));
+			Ok(())
+		}
+
+		/// Lower the supply cap of asset `id`, or set one if it has none. The cap can never be
+		/// raised or put below the current supply. Only the owner may do this.
+		#[pallet::call_index(19)]
+		#[pallet::weight(0)]
+		pub fn set_max_supply(origin: OriginFor<T>, id: T::AssetId, max_supply: T::Balance) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			let details = Self::asset_details(id)?;
+			ensure!(origin == details.owner, Error::<T>::NotTheCreator);
+
+			let supply = Self::total_supply(id);
+			ensure!(max_supply >= supply, Error::<T>::InvalidMaxSupply);
+			if let Some(current) = Self::max_supply(id) {
+				ensure!(max_supply <= current, Error::<T>::InvalidMaxSupply);
+			}
+
+			<MaxSupply<T>>::insert(id, max_supply);
+			Self::deposit_event(Event::MaxSupplySet(id, max_supply, max_supply - supply));
+			Ok(())
+		}
+
+		/// Set the smallest non-zero balance holders of asset `id` may keep. Only the owner may
+		/// do this.
+		#[pallet::call_index(24)]
+		#[pallet::weight(0)]
+		pub fn set_min_balance(origin: OriginFor<T>, id: T::AssetId, min_balance: T::Balance) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+
+			<Asset<T>>::try_mutate(id, |maybe_details| -> DispatchResult {
+				let details = maybe_details.as_mut().ok_or(Error::<T>::Unknown)?;
+				ensure!(origin == details.owner, Error::<T>::NotTheCreator);
+				details.min_balance = min_balance;
+				Ok(())
+			})?;
+			Self::deposit_event(Event::MinBalanceSet(id, min_balance));
+			Ok(())
+		}
+
+		/// Transfer asset `id` from `origin` to many recipients at once. Either every leg succeeds
+		/// or none does.
+		#[pallet::call_index(25)]
+		#[pallet::weight(Pallet::<T>::transfer_weight(legs.len() as u32))]
+		#[transactional]
+		pub fn transfer_batch(origin: OriginFor<T>,
+			id: T::AssetId,
+			legs: BoundedVec<(<<T as frame_system::Config>::Lookup as StaticLookup>::Source, T::Balance), T::MaxBatchSize>
+		) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+
+			for (target, amount) in legs {
+				let target = T::Lookup::lookup(target)?;
+				ensure!(!amount.is_zero(), Error::<T>::AmountZero);
+
+				Self::do_transfer(id, &origin, &target, amount)?;
+				Self::deposit_event(Event::Transferred(id, origin.clone(), target, amount));
+			}
+			Ok(())
+		}
+
+		/// Transfer several assets from `origin` to many recipients at once. Either every leg
+		/// succeeds or none does.
+		#[pallet::call_index(26)]
+		#[pallet::weight(Pallet::<T>::transfer_weight(legs.len() as u32))]
+		#[transactional]
+		pub fn transfer_multi(origin: OriginFor<T>,
+			legs: BoundedVec<(T::AssetId, <<T as frame_system::Config>::Lookup as StaticLookup>::Source, T::Balance), T::MaxBatchSize>
+		) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+
+			for (id, target, amount) in legs {
+				let target = T::Lookup::lookup(target)?;
+				ensure!(!amount.is_zero(), Error::<T>::AmountZero);
+
+				Self::do_transfer(id, &origin, &target, amount)?;
+				Self::deposit_event(Event::Transferred(id, origin.clone(), target, amount));
+			}
+			Ok(())
+		}
+
+		/// Set the allowance of `spender` over asset `id` owned by `owner` to `amount`, authorised
+		/// by `owner` signing
+		/// `(PERMIT_CONTEXT, genesis_hash, id, spender, amount, nonce, deadline)` off-chain, where
+		/// `genesis_hash` is the hash of this chain's block zero and `nonce` is
+		/// `permit_nonce(owner)`. Anyone may relay the permit until block `deadline`.
+		#[pallet::call_index(27)]
+		#[pallet::weight(0)]
+		pub fn permit(origin: OriginFor<T>,
+			id: T::AssetId,
+			owner: T::AccountId,
+			spender: <<T as frame_system::Config>::Lookup as StaticLookup>::Source,
+			amount: T::Balance,
+			deadline: T::BlockNumber,
+			signature: T::OffchainSignature
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+			let spender = T::Lookup::lookup(spender)?;
+			ensure!(frame_system::Pallet::<T>::block_number() <= deadline, Error::<T>::PermitExpired);
+
+			let nonce = <PermitNonces<T>>::get(&owner);
+			// The genesis hash keeps a permit from being replayed on another chain running this
+			// pallet
+			let genesis_hash = frame_system::Pallet::<T>::block_hash(T::BlockNumber::zero());
+			let message =
+				(PERMIT_CONTEXT, genesis_hash, id, &spender, amount, nonce, deadline).encode();
+			ensure!(signature.verify(&message[..], &owner), Error::<T>::BadSignature);
+
+			<PermitNonces<T>>::insert(&owner, nonce + One::one());
+			<Allowances<T>>::insert((id, &owner, &spender), amount);
+			Self::deposit_event(Event::Approved(id, owner, spender, amount));
+			Ok(())
+		}
+
+		/// Transfer `schedule.per_period * schedule.period_count` of asset `id` to `target`, locked
+		/// and released linearly according to `schedule`. The native currency cannot be vested
+		/// here, use pallet_vesting instead.
+		#[pallet::call_index(28)]
+		#[pallet::weight(0)]
+		pub fn vested_transfer(origin: OriginFor<T>,
+			id: T::AssetId,
+			target: <<T as frame_system::Config>::Lookup as StaticLookup>::Source,
+			schedule: VestingSchedule<T::BlockNumber, T::Balance>
+		) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			let target = T::Lookup::lookup(target)?;
+			ensure!(id != Zero::zero(), Error::<T>::InvalidSchedule);
+			ensure!(!schedule.period.is_zero() && schedule.period_count > 0, Error::<T>::InvalidSchedule);
+			let amount = schedule.total_amount().ok_or(Error::<T>::Overflow)?;
+			ensure!(!amount.is_zero(), Error::<T>::InvalidSchedule);
+
+			let lock = <VestingLocks<T>>::get(id, &target).checked_add(&amount).ok_or(Error::<T>::Overflow)?;
+			<VestingSchedules<T>>::try_mutate(id, &target, |schedules| {
+				schedules.try_push(schedule).map_err(|_| Error::<T>::TooManySchedules)
+			})?;
+			Self::do_transfer(id, &origin, &target, amount)?;
+			<VestingLocks<T>>::insert(id, &target, lock);
+
+			Self::deposit_event(Event::Transferred(id, origin, target.clone(), amount));
+			Self::deposit_event(Event::VestingScheduleAdded(id, target, schedule));
+			Ok(())
+		}
+
+		/// Unlock whatever has vested so far of `origin`'s balance of asset `id`, dropping
+		/// finished schedules.
+		#[pallet::call_index(29)]
+		#[pallet::weight(0)]
+		pub fn claim(origin: OriginFor<T>, id: T::AssetId) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			let now = frame_system::Pallet::<T>::block_number();
+
+			let mut schedules = <VestingSchedules<T>>::get(id, &origin);
+			schedules.retain(|schedule| !schedule.locked_amount(now).is_zero());
+			let locked = schedules.iter()
+				.fold(T::Balance::zero(), |total, schedule| total.saturating_add(schedule.locked_amount(now)));
+
+			if schedules.is_empty() {
+				<VestingSchedules<T>>::remove(id, &origin);
+				<VestingLocks<T>>::remove(id, &origin);
+			} else {
+				<VestingSchedules<T>>::insert(id, &origin, schedules);
+				<VestingLocks<T>>::insert(id, &origin, locked);
+			}
+			Self::deposit_event(Event::Claimed(id, origin, locked));
+			Ok(())
+		}
+
+		/// Allow `who` to receive permissioned asset `id`. Only the admin may do this.
+		#[pallet::call_index(31)]
+		#[pallet::weight(0)]
+		pub fn add_to_whitelist(origin: OriginFor<T>,
+			id: T::AssetId,
+			who: <<T as frame_system::Config>::Lookup as StaticLookup>::Source
+		) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			let who = T::Lookup::lookup(who)?;
+			let details = Self::asset_details(id)?;
+			ensure!(origin == details.admin, Error::<T>::NoPermission);
+
+			<Whitelist<T>>::insert(id, &who, true);
+			Self::deposit_event(Event::Whitelisted(id, who));
+			Ok(())
+		}
+
+		/// Stop `who` from receiving permissioned asset `id`. Its current balance is kept. Only
+		/// the admin may do this.
+		#[pallet::call_index(32)]
+		#[pallet::weight(0)]
+		pub fn remove_from_whitelist(origin: OriginFor<T>,
+			id: T::AssetId,
+			who: <<T as frame_system::Config>::Lookup as StaticLookup>::Source
+		) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			let who = T::Lookup::lookup(who)?;
+			let details = Self::asset_details(id)?;
+			ensure!(origin == details.admin, Error::<T>::NoPermission);
+
+			<Whitelist<T>>::remove(id, &who);
+			Self::deposit_event(Event::Unwhitelisted(id, who));
+			Ok(())
+		}
+
+		/// Take a snapshot of the balances and total supply of asset `id`, queryable later with
+		/// `balance_at` and `total_supply_at`. Only the owner or root may do this, at most
+		/// `MaxSnapshots` times per asset.
+		#[pallet::call_index(30)]
+		#[pallet::weight(0)]
+		pub fn snapshot(origin: OriginFor<T>, id: T::AssetId) -> DispatchResult {
+			let maybe_check_owner = match ensure_root(origin.clone()) {
+				Ok(()) => None,
+				Err(_) => Some(ensure_signed(origin)?),
+			};
+			let details = Self::asset_details(id)?;
+			if let Some(check_owner) = maybe_check_owner {
+				ensure!(details.owner == check_owner, Error::<T>::NotTheCreator);
+			}
+
+			let current = Self::current_snapshot(id);
+			ensure!(current < T::MaxSnapshots::get(), Error::<T>::TooManySnapshots);
+			let snapshot = current + 1;
+			<CurrentSnapshot<T>>::insert(id, snapshot);
+			Self::deposit_event(Event::SnapshotTaken(id, snapshot));
+			Ok(())
+		}
+
+		/// Start destroying asset `id`. From now on it cannot be minted, moved or burned, and
+		/// its holders and allowances can be removed with `destroy_accounts` and
+		/// `destroy_approvals` before `finish_destroy`. Only the owner may do this.
+		#[pallet::call_index(20)]
+		#[pallet::weight(0)]
+		pub fn start_destroy(origin: OriginFor<T>, id: T::AssetId) -> DispatchResult {
+			let origin = ensure_signed(origin)?;
+			Self::do_start_destroy(id, Some(origin))
+		}
+
+		/// Remove up to `RemoveItemsLimit` holders of asset `id` once its destruction started.
+		#[pallet::call_index(21)]
+		#[pallet::weight(0)]
+		pub fn destroy_accounts(origin: OriginFor<T>, id: T::AssetId) -> DispatchResult {
+			ensure_signed(origin)?;
+			Self::do_destroy_accounts(id, T::RemoveItemsLimit::get())?;
+			Ok(())
+		}
+
+		/// Remove up to `RemoveItemsLimit` allowances of asset `id` once its destruction started.
+		#[pallet::call_index(22)]
+		#[pallet::weight(0)]
+		pub fn destroy_approvals(origin: OriginFor<T>, id: T::AssetId) -> DispatchResult {
+			ensure_signed(origin)?;
+			Self::do_destroy_approvals(id, T::RemoveItemsLimit::get())?;
+			Ok(())
+		}
+
+		/// Remove the remaining storage of asset `id` once it has no holders or allowances left.
+		#[pallet::call_index(23)]
+		#[pallet::weight(0)]
+		pub fn finish_destroy(origin: OriginFor<T>, id: T::AssetId) -> DispatchResult {
+			ensure_signed(origin)?;
+			Self::do_finish_destroy(id)
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		// Module account id
+		pub fn account_id() -> T::AccountId {
+			T::PalletId::get().into_account_truncating()
+		}
+
+		// Get the asset `id` balance of `who`.
+		pub fn balance(id: T::AssetId, who: T::AccountId) -> T::Balance {
+			if id == Zero::zero() {
+				return T::Currency::free_balance(&who);
+			}
+			<Balances<T>>::get(id, who)
+		}
+		
+		// Get the total supply of an asset `id`.
+		// pub fn total_supply(id: T::AssetId) -> T::Balance {
+		// 	TotalSupply::<T>::get(id)
+		// }
+
+		// Whether asset `id` exists. Asset 0 is the native currency and always exists.
+		pub fn asset_exists(id: T::AssetId) -> bool {
+			id.is_zero() || <Asset<T>>::contains_key(id)
+		}
+
+		// Fail with `Frozen` if asset `id` or the balance of `who` in it is frozen.
+		pub fn ensure_not_frozen(id: T::AssetId, who: &T::AccountId) -> DispatchResult {
+			if id == Zero::zero() {
+				return Ok(())
+			}
+			if let Some(details) = <Asset<T>>::get(id) {
+				ensure!(!details.is_frozen, Error::<T>::Frozen);
+			}
+			ensure!(!<FrozenAccounts<T>>::get(id, who), Error::<T>::Frozen);
+			Ok(())
+		}
+
+		// Get how much more of asset `id` can be minted, or `None` if its supply is uncapped.
+		pub fn mintable(id: T::AssetId) -> Option<T::Balance> {
+			Self::max_supply(id).map(|max_supply| max_supply.saturating_sub(Self::total_supply(id)))
+		}
+
+		// Get the details of asset `id`, failing with `Unknown` if it was never issued.
+		pub fn asset_details(id: T::AssetId) -> Result<AssetDetails<T::AccountId, T::Balance>, DispatchError> {
+			Ok(<Asset<T>>::get(id).ok_or(Error::<T>::Unknown)?)
+		}
+
+		/// Create an asset class with a chosen `id` owned by `owner`, without any supply.
+		pub fn do_create(id: T::AssetId, owner: T::AccountId, min_balance: T::Balance) -> DispatchResult {
+			ensure!(!Self::asset_exists(id), Error::<T>::InUse);
+
+			<Asset<T>>::insert(id, AssetDetails {
+				min_balance,
+				..AssetDetails::new(owner.clone(), false, Zero::zero())
+			});
+			<TotalSupply<T>>::insert(id, T::Balance::zero());
+			if id >= Self::next_asset_id() {
+				<NextAssetId<T>>::put(id + One::one());
+			}
+			Self::deposit_event(Event::Created(id, owner));
+			Ok(())
+		}
+
+		/// Mark asset `id` for destruction so its accounts can be removed in batches.
+		/// When `maybe_check_owner` is given it must be the owner of the asset.
+		pub fn do_start_destroy(id: T::AssetId, maybe_check_owner: Option<T::AccountId>) -> DispatchResult {
+			ensure!(!id.is_zero(), Error::<T>::Unknown);
+			let details = Self::asset_details(id)?;
+			if let Some(check_owner) = maybe_check_owner {
+				ensure!(details.owner == check_owner, Error::<T>::NotTheCreator);
+			}
+			ensure!(details.status == AssetStatus::Live, Error::<T>::AssetNotLive);
+			ensure!(T::DestroyGuard::can_destroy(&id), Error::<T>::CannotDestroy);
+
+			<Asset<T>>::insert(id, AssetDetails { status: AssetStatus::Destroying, ..details });
+			Self::deposit_event(Event::DestructionStarted(id));
+			Ok(())
+		}
+
+		// Fail with `LiveAsset` unless the destruction of asset `id` has been started.
+		fn ensure_destroying(id: T::AssetId) -> DispatchResult {
+			let details = Self::asset_details(id)?;
+			ensure!(details.status == AssetStatus::Destroying, Error::<T>::LiveAsset);
+			Ok(())
+		}
+
+		/// Remove up to `max_items` holders and frozen entries of asset `id`, returning how many
+		/// were removed.
+		pub fn do_destroy_accounts(id: T::AssetId, max_items: u32) -> Result<u32, DispatchError> {
+			Self::ensure_destroying(id)?;
+			let mut removed = 0u32;
+			for (who, balance) in <Balances<T>>::drain_prefix(id).take(max_items as usize) {
+				<AccountAssets<T>>::remove(&who, id);
+				<FrozenAccounts<T>>::remove(id, &who);
+				<VestingSchedules<T>>::remove(id, &who);
+				<VestingLocks<T>>::remove(id, &who);
+				<Whitelist<T>>::remove(id, &who);
+				<TotalHeld<T>>::remove(id, &who);
+				let _ = <Holds<T>>::clear_prefix((id, who.clone()), u32::MAX, None);
+				// Destruction was already agreed to, so handlers are told but cannot veto it
+				let _ = T::OnBurn::on_burn(&id, &who, &balance);
+				<TotalSupply<T>>::mutate(id, |supply| *supply = supply.saturating_sub(balance));
+				removed += 1;
+			}
+			if removed < max_items {
+				removed += <FrozenAccounts<T>>::drain_prefix(id).take((max_items - removed) as usize).count() as u32;
+			}
+			if removed < max_items {
+				removed += <Whitelist<T>>::drain_prefix(id).take((max_items - removed) as usize).count() as u32;
+			}
+			if removed < max_items {
+				removed += <BalanceSnapshots<T>>::drain_prefix(id).take((max_items - removed) as usize).count() as u32;
+			}
+			Self::deposit_event(Event::AccountsDestroyed(id, removed));
+			Ok(removed)
+		}
+
+		/// Remove up to `max_items` allowances of asset `id`, returning how many were removed.
+		pub fn do_destroy_approvals(id: T::AssetId, max_items: u32) -> Result<u32, DispatchError> {
+			Self::ensure_destroying(id)?;
+			let removed = <Allowances<T>>::drain_prefix((id,)).take(max_items as usize).count() as u32;
+			Self::deposit_event(Event::ApprovalsDestroyed(id, removed));
+			Ok(removed)
+		}
+
+		/// Remove the remaining storage of asset `id` once all holders and allowances are gone,
+		/// returning the deposits to its owner.
+		pub fn do_finish_destroy(id: T::AssetId) -> DispatchResult {
+			Self::ensure_destroying(id)?;
+			let details = Self::asset_details(id)?;
+			ensure!(<Balances<T>>::iter_prefix(id).next().is_none(), Error::<T>::InUse);
+			ensure!(<FrozenAccounts<T>>::iter_prefix(id).next().is_none(), Error::<T>::InUse);
+			ensure!(<Whitelist<T>>::iter_prefix(id).next().is_none(), Error::<T>::InUse);
+			ensure!(<BalanceSnapshots<T>>::iter_prefix(id).next().is_none(), Error::<T>::InUse);
+			ensure!(<Allowances<T>>::iter_prefix((id,)).next().is_none(), Error::<T>::InUse);
+
+			let deposit = <Metadata<T>>::take(id)
+				.map(|metadata| metadata.deposit)
+				.unwrap_or_default()
+				.saturating_add(details.deposit);
+			<Asset<T>>::remove(id);
+			<TotalSupply<T>>::remove(id);
+			<MaxSupply<T>>::remove(id);
+			<CurrentSnapshot<T>>::remove(id);
+			<SupplySnapshots<T>>::remove(id);
+			if !deposit.is_zero() {
+				T::Currency::unreserve(&details.owner, deposit);
+				Self::deposit_event(Event::DepositUnreserved(id, details.owner, deposit));
+			}
+			Self::deposit_event(Event::Destroyed(id));
+			Ok(())
+		}
+
+		// Fail with `AssetNotLive` if asset `id` is being destroyed.
+		pub fn ensure_live(id: T::AssetId) -> DispatchResult {
+			if let Some(details) = <Asset<T>>::get(id) {
+				ensure!(details.status == AssetStatus::Live, Error::<T>::AssetNotLive);
+			}
+			Ok(())
+		}
+
+		// Increase the balance of `target` and the total supply of asset `id`. The native currency
+		// goes through `T::Currency` so its total issuance stays correct.
+		fn do_mint(id: T::AssetId, target: &T::AccountId, amount: T::Balance) -> DispatchResult {
+			T::OnMint::on_mint(&id, target, &amount)?;
+			if id == Zero::zero() {
+				T::Currency::total_issuance().checked_add(&amount).ok_or(Error::<T>::Overflow)?;
+				// Nothing is deposited into a new account below the existential deposit
+				let minted = T::Currency::deposit_creating(target, amount);
+				ensure!(minted.peek() == amount, Error::<T>::BelowMinimum);
+				return Ok(())
+			}
+			Self::ensure_live(id)?;
+			Self::ensure_whitelisted(id, target)?;
+			let supply = <TotalSupply<T>>::get(id).checked_add(&amount).ok_or(Error::<T>::Overflow)?;
+			if let Some(max_supply) = Self::max_supply(id) {
+				ensure!(supply <= max_supply, Error::<T>::MaxSupplyExceeded);
+			}
+			let balance = <Balances<T>>::get(id, target).checked_add(&amount).ok_or(Error::<T>::Overflow)?;
+			Self::ensure_min_balance(id, target, balance)?;
+
+			Self::record_supply_snapshot(id);
+			<TotalSupply<T>>::insert(id, supply);
+			Self::write_balance(id, target, balance);
+			Ok(())
+		}
+
+		// Decrease the balance of `target` and the total supply of asset `id`. Native withdrawals
+		// respect locks and keep `target` alive.
+		fn do_burn(id: T::AssetId, target: &T::AccountId, amount: T::Balance) -> DispatchResult {
+			T::OnBurn::on_burn(&id, target, &amount)?;
+			if id == Zero::zero() {
+				let _ = T::Currency::withdraw(
+					target,
+					amount,
+					WithdrawReasons::TRANSFER,
+					ExistenceRequirement::KeepAlive,
+				)?;
+				return Ok(())
+			}
+			Self::ensure_live(id)?;
+			Self::ensure_not_frozen(id, target)?;
+			let balance = <Balances<T>>::get(id, target).checked_sub(&amount).ok_or(Error::<T>::BalanceLow)?;
+			Self::ensure_unlocked(id, target, balance)?;
+			let supply = <TotalSupply<T>>::get(id).checked_sub(&amount).ok_or(Error::<T>::Overflow)?;
+			Self::ensure_min_balance(id, target, balance)?;
+
+			Self::write_balance(id, target, balance);
+			Self::record_supply_snapshot(id);
+			<TotalSupply<T>>::insert(id, supply);
+			Ok(())
+		}
+
+		// Move `amount` of asset `id` from `source` to `target`. Both balances are checked before
+		// anything is written. Native transfers respect locks and keep `source` alive.
+		fn do_transfer(
+			id: T::AssetId,
+			source: &T::AccountId,
+			target: &T::AccountId,
+			amount: T::Balance,
+		) -> DispatchResult {
+			T::OnTransfer::on_transfer(&id, source, target, &amount)?;
+			if id == Zero::zero() {
+				ensure!(T::Currency::free_balance(source) >= amount, Error::<T>::BalanceLow);
+				return T::Currency::transfer(source, target, amount, ExistenceRequirement::KeepAlive)
+			}
+			Self::ensure_live(id)?;
+			Self::ensure_not_frozen(id, source)?;
+			let source_balance = <Balances<T>>::get(id, source).checked_sub(&amount).ok_or(Error::<T>::BalanceLow)?;
+			Self::ensure_unlocked(id, source, source_balance)?;
+			if source == target {
+				return Ok(())
+			}
+			Self::ensure_whitelisted(id, target)?;
+			let target_balance = <Balances<T>>::get(id, target).checked_add(&amount).ok_or(Error::<T>::Overflow)?;
+			Self::ensure_min_balance(id, source, source_balance)?;
+			Self::ensure_min_balance(id, target, target_balance)?;
+
+			Self::write_balance(id, source, source_balance);
+			Self::write_balance(id, target, target_balance);
+			Ok(())
+		}
+
+		// Weight of `legs` transfers: each reads the asset details, the frozen flag, both balances,
+		// the vesting lock, the held total, the current snapshot, both snapshot records and the
+		// receiver's whitelist entry, and writes both balances, their holder index and their
+		// snapshot records.
+		pub(crate) fn transfer_weight(legs: u32) -> Weight {
+			T::DbWeight::get().reads_writes(10, 6).saturating_mul(legs as u64)
+		}
+
+		// Fail with `NotWhitelisted` if asset `id` is permissioned and `who` may not receive it.
+		// The pallet account is exempt so the asset can be pooled in the market.
+		fn ensure_whitelisted(id: T::AssetId, who: &T::AccountId) -> DispatchResult {
+			if *who == Self::account_id() {
+				return Ok(())
+			}
+			let is_permissioned = Self::asset(id).map(|details| details.is_permissioned).unwrap_or(false);
+			ensure!(!is_permissioned || <Whitelist<T>>::get(id, who), Error::<T>::NotWhitelisted);
+			Ok(())
+		}
+
+		// Fail if `who` would keep less of asset `id` than vesting locks plus its holds.
+		pub(crate) fn ensure_unlocked(id: T::AssetId, who: &T::AccountId, balance: T::Balance) -> DispatchResult {
+			let locked = <VestingLocks<T>>::get(id, who);
+			ensure!(balance >= locked, Error::<T>::VestingLocked);
+			ensure!(balance - locked >= <TotalHeld<T>>::get(id, who), Error::<T>::BalanceOnHold);
+			Ok(())
+		}
+
+		/// Put `amount` of `who`'s balance of asset `id` on hold for `reason`. It stays attributed
+		/// to `who` but cannot be spent until released. The native currency is reserved instead.
+		#[transactional]
+		pub fn hold(reason: HoldReason, id: T::AssetId, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
+			let held = <Holds<T>>::get((id, who, reason)).checked_add(&amount).ok_or(Error::<T>::Overflow)?;
+			if id == Zero::zero() {
+				T::Currency::reserve(who, amount)?;
+			} else {
+				Self::ensure_live(id)?;
+				Self::ensure_not_frozen(id, who)?;
+				let total_held = <TotalHeld<T>>::get(id, who).checked_add(&amount).ok_or(Error::<T>::Overflow)?;
+				let spendable = <Balances<T>>::get(id, who).saturating_sub(<VestingLocks<T>>::get(id, who));
+				ensure!(spendable >= total_held, Error::<T>::BalanceLow);
+				<TotalHeld<T>>::insert(id, who, total_held);
+			}
+			<Holds<T>>::insert((id, who, reason), held);
+			Self::deposit_event(Event::Held(id, who.clone(), reason, amount));
+			Ok(())
+		}
+
+		/// Make `amount` of `who`'s balance of asset `id` held for `reason` spendable again.
+		#[transactional]
+		pub fn release(reason: HoldReason, id: T::AssetId, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+			Self::decrease_hold(reason, id, who, amount)?;
+			if id == Zero::zero() {
+				let unreserved = T::Currency::unreserve(who, amount);
+				ensure!(unreserved.is_zero(), Error::<T>::InsufficientHold);
+			}
+			Self::deposit_event(Event::Released(id, who.clone(), reason, amount));
+			Ok(())
+		}
+
+		/// Burn `amount` of `who`'s balance of asset `id` held for `reason`, even while `who` is
+		/// frozen or left below the minimum balance.
+		#[transactional]
+		pub fn slash(reason: HoldReason, id: T::AssetId, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+			Self::decrease_hold(reason, id, who, amount)?;
+			if id == Zero::zero() {
+				let (_, unslashed) = T::Currency::slash_reserved(who, amount);
+				ensure!(unslashed.is_zero(), Error::<T>::InsufficientHold);
+			} else {
+				// Held funds are owed regardless of freezes, locks and the minimum balance, and
+				// handlers are told of the burn but cannot veto it
+				Self::ensure_live(id)?;
+				let balance = <Balances<T>>::get(id, who).checked_sub(&amount).ok_or(Error::<T>::BalanceLow)?;
+				let _ = T::OnBurn::on_burn(&id, who, &amount);
+				Self::write_balance(id, who, balance);
+				Self::record_supply_snapshot(id);
+				<TotalSupply<T>>::mutate(id, |supply| *supply = supply.saturating_sub(amount));
+			}
+			Self::deposit_event(Event::Slashed(id, who.clone(), reason, amount));
+			Ok(())
+		}
+
+		/// Move `amount` of `source`'s balance of asset `id` held for `reason` to `dest`, where it
+		/// stays held for the same reason if `on_hold` is set.
+		#[transactional]
+		pub fn transfer_on_hold(
+			reason: HoldReason,
+			id: T::AssetId,
+			source: &T::AccountId,
+			dest: &T::AccountId,
+			amount: T::Balance,
+			on_hold: bool,
+		) -> DispatchResult {
+			Self::decrease_hold(reason, id, source, amount)?;
+			if id == Zero::zero() {
+				let status = if on_hold { BalanceStatus::Reserved } else { BalanceStatus::Free };
+				let unmoved = T::Currency::repatriate_reserved(source, dest, amount, status)?;
+				ensure!(unmoved.is_zero(), Error::<T>::InsufficientHold);
+				if on_hold {
+					<Holds<T>>::mutate((id, dest, reason), |held| *held = held.saturating_add(amount));
+				}
+			} else {
+				Self::do_transfer(id, source, dest, amount)?;
+				if on_hold {
+					Self::hold(reason, id, dest, amount)?;
+				}
+			}
+			Self::deposit_event(Event::TransferredOnHold(id, source.clone(), dest.clone(), reason, amount));
+			Ok(())
+		}
+
+		// Take `amount` off what `who` holds of asset `id` for `reason`.
+		fn decrease_hold(reason: HoldReason, id: T::AssetId, who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
+			let held = <Holds<T>>::get((id, who, reason)).checked_sub(&amount).ok_or(Error::<T>::InsufficientHold)?;
+			match held.is_zero() {
+				true => <Holds<T>>::remove((id, who, reason)),
+				false => <Holds<T>>::insert((id, who, reason), held),
+			}
+			if id != Zero::zero() {
+				<TotalHeld<T>>::mutate_exists(id, who, |total| {
+					*total = total.map(|total| total.saturating_sub(amount)).filter(|total| !total.is_zero());
+				});
+			}
+			Ok(())
+		}
+
+		// Fail with `BelowMinimum` if `balance` is non-zero but below the minimum balance of asset
+		// `id`. The pallet account is exempt so pool reserves may run low.
+		fn ensure_min_balance(id: T::AssetId, who: &T::AccountId, balance: T::Balance) -> DispatchResult {
+			if balance.is_zero() || *who == Self::account_id() {
+				return Ok(())
+			}
+			let min_balance = Self::asset(id).map(|details| details.min_balance).unwrap_or_default();
+			ensure!(balance >= min_balance, Error::<T>::BelowMinimum);
+			Ok(())
+		}
+
+		// Write the balance of `who` in asset `id`, reaping the entry and its index once it is
+		// zero.
+		fn write_balance(id: T::AssetId, who: &T::AccountId, balance: T::Balance) {
+			Self::record_balance_snapshot(id, who);
+			if balance.is_zero() {
+				<Balances<T>>::remove(id, who);
+				<AccountAssets<T>>::remove(who, id);
+			} else {
+				<Balances<T>>::insert(id, who, balance);
+				<AccountAssets<T>>::insert(who, id, ());
+			}
+		}
+
+		// Record the balance of `who` in asset `id` for the current snapshot before it first
+		// changes.
+		fn record_balance_snapshot(id: T::AssetId, who: &T::AccountId) {
+			let snapshot = Self::current_snapshot(id);
+			let mut records = <BalanceSnapshots<T>>::get(id, who);
+			if records.last().map_or(0, |(at, _)| *at) < snapshot {
+				// A record per snapshot at most, so the bound always leaves room
+				let _ = records.try_push((snapshot, <Balances<T>>::get(id, who)));
+				<BalanceSnapshots<T>>::insert(id, who, records);
+			}
+		}
+
+		// Record the total supply of asset `id` for the current snapshot before it first changes.
+		fn record_supply_snapshot(id: T::AssetId) {
+			let snapshot = Self::current_snapshot(id);
+			let mut records = <SupplySnapshots<T>>::get(id);
+			if records.last().map_or(0, |(at, _)| *at) < snapshot {
+				let _ = records.try_push((snapshot, <TotalSupply<T>>::get(id)));
+				<SupplySnapshots<T>>::insert(id, records);
+			}
+		}
+
+		// The first of the sorted `records` taken at or after `snapshot`, which holds the value
+		// from before the change. Without one the value has not changed since.
+		fn recorded_at(records: &[(u32, T::Balance)], snapshot: u32) -> Option<T::Balance> {
+			let index = records.partition_point(|(at, _)| *at < snapshot);
+			records.get(index).map(|(_, value)| *value)
+		}
+
+		/// The balance of `who` in asset `id` when `snapshot` was taken.
+		pub fn balance_at(id: T::AssetId, who: &T::AccountId, snapshot: u32) -> Result<T::Balance, DispatchError> {
+			ensure!(snapshot > 0 && snapshot <= Self::current_snapshot(id), Error::<T>::UnknownSnapshot);
+			let recorded = Self::recorded_at(&<BalanceSnapshots<T>>::get(id, who), snapshot);
+			Ok(recorded.unwrap_or_else(|| <Balances<T>>::get(id, who)))
+		}
+
+		/// The total supply of asset `id` when `snapshot` was taken.
+		pub fn total_supply_at(id: T::AssetId, snapshot: u32) -> Result<T::Balance, DispatchError> {
+			ensure!(snapshot > 0 && snapshot <= Self::current_snapshot(id), Error::<T>::UnknownSnapshot);
+			let recorded = Self::recorded_at(&<SupplySnapshots<T>>::get(id), snapshot);
+			Ok(recorded.unwrap_or_else(|| <TotalSupply<T>>::get(id)))
+		}
+
+		/// List every asset held by `who` with its balance, starting with the native currency.
+		pub fn account_assets(who: &T::AccountId) -> Vec<(T::AssetId, T::Balance)> {
+			let mut assets = Vec::new();
+			let native = T::Currency::free_balance(who);
+			if !native.is_zero() {
+				assets.push((Zero::zero(), native));
+			}
+			assets.extend(
+				<AccountAssets<T>>::iter_key_prefix(who).map(|id| (id, <Balances<T>>::get(id, who))),
+			);
+			assets
+		}
+
+		#[transactional]
+		pub fn mint_from_system(
+			id: &T::AssetId,
+			target: &T::AccountId,
+			amount: &T::Balance,
+		) -> DispatchResult {
+			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
+			Self::do_mint(*id, target, *amount)?;
+			Self::deposit_event(Event::Minted(*id, target.clone(), *amount));
+			Ok(())
+		}
+
+		#[transactional]
+		pub fn burn_from_system(
+			id: &T::AssetId,
+			target: &T::AccountId,
+			amount: &T::Balance,
+		) -> DispatchResult {
+			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
+			Self::do_burn(*id, target, *amount)?;
+			Self::deposit_event(Event::Burned(*id, target.clone(), *amount));
+			Ok(())
+		}
+
+		#[transactional]
+		pub fn transfer_system(
+			id: &T::AssetId,
+			source: &T::AccountId,
+			target: &T::AccountId,
+			amount: &T::Balance,
+		) -> DispatchResult {
+			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
+			Self::do_transfer(*id, source, target, *amount)?;
+			Self::deposit_event(Event::Transferred(*id, source.clone(), target.clone(), *amount));
+			Ok(())
+		}
+
+		#[transactional]
+		pub fn transfer_from_system(
+			id: &T::AssetId,
+			target: &T::AccountId,
+			amount: &T::Balance,
+		) -> DispatchResult {
+			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
+			let module_account = Self::account_id();
+			Self::do_transfer(*id, &module_account, target, *amount)?;
+			Self::deposit_event(Event::TransferredFromSystem(*id, target.clone(), *amount));
+			Ok(())
+		}
+
+		#[transactional]
+		pub fn transfer_to_system(
+			id: &T::AssetId,
+			source: &T::AccountId,
+			amount: &T::Balance,
+		) -> DispatchResult {
+			ensure!(!amount.is_zero(), Error::<T>::AmountZero);
+			let module_account = Self::account_id();
+			Self::do_transfer(*id, source, &module_account, *amount)?;
+			Self::deposit_event(Event::TransferredToSystem(*id, source.clone(), *amount));
+			Ok(())
+		}
+
+		pub fn issue_from_system(total: T::Balance) -> Result<T::AssetId, DispatchError> {
+			let id = Self::next_asset_id();
+			let module_account  = Self::account_id();
+			<NextAssetId<T>>::mutate(|id| {
+				*id += One::one();
+			});
+			if !total.is_zero() {
+				T::OnMint::on_mint(&id, &module_account, &total)?;
+			}
+			<TotalSupply<T>>::insert(id, total);
+			Self::write_balance(id, &module_account, total);
+			<Asset<T>>::insert(id, AssetDetails::new(module_account, true, Zero::zero()));
+			Self::deposit_event(Event::IssuedBySystem(id, total));
+			Ok(id)
+		}
+
+		/// Set metadata for an asset issued by the system. Name and symbol are truncated to
+		/// `StringLimit` instead of failing.
+		pub fn set_metadata_from_system(
+			id: &T::AssetId,
+			mut name: Vec<u8>,
+			mut symbol: Vec<u8>,
+			decimals: u8,
+		) -> DispatchResult {
+			let limit = T::StringLimit::get() as usize;
+			name.truncate(limit);
+			symbol.truncate(limit);
+			let bounded_name: BoundedVec<u8, T::StringLimit> =
+				name.clone().try_into().map_err(|_| Error::<T>::BadMetadata)?;
+			let bounded_symbol: BoundedVec<u8, T::StringLimit> =
+				symbol.clone().try_into().map_err(|_| Error::<T>::BadMetadata)?;
+
+			<Metadata<T>>::insert(*id, AssetMetadata { deposit: Zero::zero(), name: bounded_name, symbol: bounded_symbol, decimals });
+			Self::deposit_event(Event::MetadataSet(*id, name, symbol, decimals));
+			Ok(())
+		}
+
+		// Get the symbol of asset `id`, falling back to its decimal identifier when no metadata
+		// is set.
+		pub fn symbol(id: &T::AssetId) -> Vec<u8> {
+			match Self::metadata(id) {
+				Some(metadata) => metadata.symbol.into_inner(),
+				None => {
+					let mut n: u128 = (*id).unique_saturated_into();
+					let mut digits = Vec::new();
+					loop {
+						digits.push(b'0' + (n % 10) as u8);
+						n /= 10;
+						if n == 0 {
+							break;
+						}
+					}
+					digits.reverse();
+					digits
+				},
+			}
+		}
+
+		// Get the decimals of asset `id`, or zero when no metadata is set.
+		pub fn decimals(id: &T::AssetId) -> u8 {
+			Self::metadata(id).map(|metadata| metadata.decimals).unwrap_or_default()
+		}
+	}
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::VestingSchedule;
+
+	fn schedule() -> VestingSchedule<u64, u128> {
+		VestingSchedule { start: 100, period: 10, per_period: 5, period_count: 4 }
+	}
+
+	#[test]
+	fn total_amount_is_every_period() {
+		assert_eq!(schedule().total_amount(), Some(20));
+		let overflowing = VestingSchedule { per_period: u128::MAX, ..schedule() };
+		assert_eq!(overflowing.total_amount(), None);
+	}
+
+	#[test]
+	fn everything_is_locked_before_start() {
+		assert_eq!(schedule().locked_amount(0), 20);
+		assert_eq!(schedule().locked_amount(99), 20);
+		assert_eq!(schedule().locked_amount(100), 20);
+	}
+
+	#[test]
+	fn periods_release_only_once_fully_elapsed() {
+		assert_eq!(schedule().locked_amount(109), 20);
+		assert_eq!(schedule().locked_amount(110), 15);
+		assert_eq!(schedule().locked_amount(125), 10);
+		assert_eq!(schedule().locked_amount(139), 5);
+	}
+
+	#[test]
+	fn nothing_is_locked_once_every_period_elapsed() {
+		assert_eq!(schedule().locked_amount(140), 0);
+		assert_eq!(schedule().locked_amount(1_000), 0);
+	}
+
+	#[test]
+	fn elapsed_periods_beyond_u32_saturate() {
+		let schedule: VestingSchedule<u64, u128> =
+			VestingSchedule { start: 0, period: 1, per_period: 1, period_count: u32::MAX };
+		assert_eq!(schedule.locked_amount(u32::MAX as u64 - 1), 1);
+		assert_eq!(schedule.locked_amount(u32::MAX as u64 + 1), 0);
+		assert_eq!(schedule.locked_amount(u64::MAX), 0);
+	}
+}
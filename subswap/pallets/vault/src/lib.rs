@@ -136,8 +136,8 @@ pub mod pallet {
             // Check whether CDP is valid
             ensure!(result, Error::<T>::InvalidCDP);
             
-            // Send collateral to Standard Protocol
-            token::Pallet::<T>::transfer_to_system(&collateral_id, &origin, &collateral_amount)?;
+            // Hold collateral so it stays with the sender but cannot be spent
+            token::Pallet::<T>::hold(token::HoldReason::VaultCollateral, collateral_id, &origin, collateral_amount)?;
 
             // Update CDP
             <Vault<T>>::mutate((origin.clone(), collateral_id), |vlt|{
@@ -281,6 +281,11 @@ impl pallet_sudo::Config for Runtime {
 parameter_types! {
 	pub const TransactionByteFee: Balance = 1;
 	pub const AssetPalletId: PalletId = PalletId(*b"stnd/ast");
+	pub const FeePalletId: PalletId = PalletId(*b"stnd/fee");
+	pub FeeTiers: Vec<u32> = sp_std::vec![5, 30, 100];
+	pub const AssetDeposit: Balance = 100 * EXISTENTIAL_DEPOSIT;
+	pub const MetadataDepositBase: Balance = 10 * EXISTENTIAL_DEPOSIT;
+	pub const MetadataDepositPerByte: Balance = EXISTENTIAL_DEPOSIT;
 }
 /// Configure the pallet-template in pallets/token.
 impl pallet_token::Config for Runtime {
@@ -289,6 +294,20 @@ impl pallet_token::Config for Runtime {
 	type AssetId = u32;
 	type WeightInfo = ();
 	type Currency = Balances;
+	type StringLimit = ConstU32<50>;
+	type RemoveItemsLimit = ConstU32<1000>;
+	type DestroyGuard = MarketModule;
+	type AssetDeposit = AssetDeposit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type MaxBatchSize = ConstU32<64>;
+	type OffchainSignature = Signature;
+	type OffchainPublic = <Signature as Verify>::Signer;
+	type MaxVestingSchedules = ConstU32<16>;
+	type MaxSnapshots = ConstU32<100>;
+	type OnTransfer = ();
+	type OnMint = ();
+	type OnBurn = ();
 }
 
 /// Configure the pallet-template in pallets/vault.
@@ -305,6 +324,11 @@ impl pallet_vault::Config for Runtime {
 /// Configure the pallet-template in pallets/market.
 impl pallet_market::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
+	type MaxPathLength = ConstU32<5>;
+	type FeeTiers = FeeTiers;
+	type DefaultFee = ConstU32<30>;
+	type GovernanceOrigin = frame_system::EnsureRoot<AccountId>;
+	type ProtocolFeeDivisor = ConstU32<6>;
 	// type PalletId = AssetPalletId;
 	// type AssetId = u32;
 	// type WeightInfo = ();
@@ -319,6 +343,12 @@ impl pallet_oracle::Config for Runtime {
 	// type Currency = Balances;
 }
 
+/// Configure the fee pallet in pallets/fee.
+impl pallet_fee::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type FeePalletId = FeePalletId;
+}
+
 // Create the runtime by composing the FRAME pallets that were previously configured.
 construct_runtime!(
 	pub struct Runtime
@@ -340,6 +370,7 @@ construct_runtime!(
 		VaultModule: pallet_vault,
 		MarketModule: pallet_market,
 		OracleModule: pallet_oracle,
+		FeeModule: pallet_fee,
 	}
 );
 
@@ -358,7 +389,7 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_fee::ChargeAssetTxPayment<Runtime>,
 );
 
 /// Unchecked extrinsic type as expected by this runtime.
@@ -538,6 +569,20 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_token_runtime_api::TokenApi<Block, AccountId, u32, Balance> for Runtime {
+		fn account_assets(who: AccountId) -> Vec<(u32, Balance)> {
+			TokenModule::account_assets(&who)
+		}
+
+		fn balance_at(asset: u32, who: AccountId, snapshot: u32) -> Option<Balance> {
+			TokenModule::balance_at(asset, &who, snapshot).ok()
+		}
+
+		fn total_supply_at(asset: u32, snapshot: u32) -> Option<Balance> {
+			TokenModule::total_supply_at(asset, snapshot).ok()
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (
@@ -110,7 +110,7 @@ export default function Register(props: IRegisterProps) {
       await apiBC.tx.marketModule
       // fixed value
       // dynamic value
-        .mintLiquidity(arg1, arg2, arg3, arg4)
+        .mintLiquidity(arg1, arg2, arg3, arg4, null, 0, null)
         .signAndSend(
           accounts[0].address,
           { signer: injector?.signer },
@@ -157,7 +157,7 @@ export default function Register(props: IRegisterProps) {
       await apiBC.tx.marketModule
       // fixed value
       // dynamic value
-        .burnLiquidity(arg1, arg2)
+        .burnLiquidity(arg1, arg2, 0, 0, null)
         .signAndSend(
           accounts[0].address,
           { signer: injector?.signer },
@@ -204,7 +204,7 @@ export default function Register(props: IRegisterProps) {
       await apiBC.tx.marketModule
       // fixed value
       // dynamic value
-        .swap(arg1, arg2, arg3)
+        .swap(arg1, arg2, arg3, 0, null)
         .signAndSend(
           accounts[0].address,
           { signer: injector?.signer },
@@ -118,7 +118,7 @@ export default function Register(props: IRegisterProps) {
         await apiBC.tx.tokenModule
           // fixed value
           // dynamic value
-          .issue(number)
+          .issue(number, null, false)
           .signAndSend(
             accounts[0].address,
             { signer: injector?.signer },
@@ -303,7 +303,7 @@ export default function Register(props: IRegisterProps) {
         await apiBC.tx.tokenModule
           // fixed value
           // dynamic value
-          .destroy(arg1)
+          .startDestroy(arg1)
           .signAndSend(
             accounts[0].address,
             { signer: injector?.signer },
@@ -350,12 +350,12 @@ export default function Register(props: IRegisterProps) {
   }
 
   const balances = async (arg: any) => {
-    const res = await apiBC.query.tokenModule.balances(arg);
+    const res = await apiBC.query.tokenModule.balances(...arg);
     setResult3(res.toHuman());
   }
 
   const creator = async (arg: any) => {
-    const res = await apiBC.query.tokenModule.creator(arg);
+    const res = await apiBC.query.tokenModule.asset(arg);
     setResult4(res.toHuman());
   }
 